@@ -0,0 +1,18 @@
+use actix_web::{FromRequest, HttpRequest, dev::Payload};
+
+/// The API version a request was routed through, registered as app data on
+/// each versioned scope so handlers can be shared between versions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl FromRequest for ApiVersion {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        std::future::ready(Ok(req.app_data::<ApiVersion>().copied().unwrap_or(ApiVersion::V1)))
+    }
+}
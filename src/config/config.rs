@@ -2,13 +2,41 @@
 pub struct Config {
     pub url: String,
     pub port: String,
-    pub db: std::sync::Mutex<TempDatabase>,
+    pub max_batch_size: usize,
+    pub max_payload_bytes: usize,
+    pub max_import_size: usize,
+    pub trash_retention_secs: u64,
+    pub nl_rules_path: Option<String>,
+    pub nl_rules: std::sync::RwLock<std::sync::Arc<Vec<crate::query::rules::PhraseRule>>>,
+    pub snapshot_path: Option<String>,
+    pub snapshot_interval_secs: u64,
+    pub wal: Option<crate::config::wal::Wal>,
+    pub snapshot_lock: std::sync::Mutex<()>,
+    pub events: crate::config::events::EventLog,
+    pub webhooks: crate::config::webhooks::WebhookRegistry,
+    pub db: SharedDatabase,
+    pub collections: std::sync::Mutex<std::collections::HashMap<String, SharedDatabase>>,
 }
 
-#[derive(Debug, Clone)]
+pub type SharedDatabase = std::sync::Arc<std::sync::Mutex<TempDatabase>>;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct TempDatabase{
     pub processed_strings_hash: Vec<String>,
     pub processed_results: Vec<AnalysisResult>,
+    pub trash: Vec<DeletedRecord>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct DeletedRecord {
+    pub result: AnalysisResult,
+    pub deleted_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug)]
+pub enum RestoreError {
+    NotInTrash,
+    AlreadyExists,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
@@ -20,7 +48,11 @@ pub struct AnalysisResult {
     pub word_count: usize,
     pub sha256_hash: String,
     pub character_frequency_map: std::collections::HashMap<char, usize>,
-    pub created_at: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
 }
 
 impl TempDatabase {
@@ -28,11 +60,87 @@ impl TempDatabase {
         Self {
             processed_strings_hash: Vec::new(),
             processed_results: Vec::new(),
+            trash: Vec::new(),
+        }
+    }
+
+    /// Adds a string, replacing any stored string with the same hash.
+    /// Returns `true` when one was replaced.
+    pub fn upsert(&mut self, result: AnalysisResult) -> bool {
+        match self.find_mut(&result.sha256_hash) {
+            Some(existing) => {
+                *existing = result;
+                return true;
+            },
+            None => {
+                self.processed_strings_hash.push(result.sha256_hash.clone());
+                self.processed_results.push(result);
+                return false;
+            }
+        }
+    }
+
+    /// Moves the stored string with the given hash into the trash.
+    /// Returns `false` when no such string is stored.
+    pub fn soft_delete(&mut self, hash: &str, deleted_at: chrono::DateTime<chrono::Utc>) -> bool {
+        let Some(index) = self.processed_strings_hash.iter().position(|h| h == hash) else {
+            return false;
+        };
+
+        self.processed_strings_hash.remove(index);
+        let result = self.processed_results.remove(index);
+        self.trash.push(DeletedRecord { result, deleted_at });
+        return true;
+    }
+
+    /// Moves every stored string whose hash is in `hashes` into the trash.
+    pub fn soft_delete_many(&mut self, hashes: &std::collections::HashSet<&String>, deleted_at: chrono::DateTime<chrono::Utc>) {
+        let (deleted, kept): (Vec<AnalysisResult>, Vec<AnalysisResult>) = self
+            .processed_results
+            .drain(..)
+            .partition(|res| hashes.contains(&res.sha256_hash));
+
+        self.processed_strings_hash = kept.iter().map(|res| res.sha256_hash.clone()).collect();
+        self.processed_results = kept;
+        self.trash.extend(deleted.into_iter().map(|result| DeletedRecord { result, deleted_at }));
+    }
+
+    /// Checks that a trashed string can be moved back into the store,
+    /// returning its position in the trash.
+    pub fn restorable(&self, hash: &str) -> Result<usize, RestoreError> {
+        let Some(index) = self.trash.iter().position(|rec| rec.result.sha256_hash == hash) else {
+            return Err(RestoreError::NotInTrash);
+        };
+        if self.processed_strings_hash.iter().any(|h| h == hash) {
+            return Err(RestoreError::AlreadyExists);
         }
+        return Ok(index);
+    }
+
+    /// Moves a trashed string back into the store.
+    pub fn restore(&mut self, hash: &str) -> Result<AnalysisResult, RestoreError> {
+        let index = self.restorable(hash)?;
+        let record = self.trash.remove(index);
+        self.processed_strings_hash.push(record.result.sha256_hash.clone());
+        self.processed_results.push(record.result.clone());
+        return Ok(record.result);
+    }
+
+    /// Looks up a stored string by its hash.
+    pub fn find_mut(&mut self, hash: &str) -> Option<&mut AnalysisResult> {
+        return self.processed_results.iter_mut().find(|res| res.sha256_hash == hash);
+    }
+
+    /// Permanently drops trashed strings deleted before `cutoff`, returning how many were purged.
+    pub fn purge_trash(&mut self, cutoff: chrono::DateTime<chrono::Utc>) -> usize {
+        let before = self.trash.len();
+        self.trash.retain(|rec| rec.deleted_at >= cutoff);
+        return before - self.trash.len();
     }
 }
 
 impl AnalysisResult {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         length: usize,
         is_palindrome: bool,
@@ -41,7 +149,7 @@ impl AnalysisResult {
         word_count: usize,
         sha256_hash: String,
         character_frequency_map: std::collections::HashMap<char, usize>,
-        created_at: String,
+        created_at: chrono::DateTime<chrono::Utc>,
     ) -> Self {
         Self {
             length,
@@ -52,6 +160,8 @@ impl AnalysisResult {
             sha256_hash,
             character_frequency_map,
             created_at,
+            tags: Vec::new(),
+            metadata: serde_json::Map::new(),
         }
     }
 }
@@ -60,14 +170,128 @@ impl Config {
     pub fn init() -> Config {
         let port = std::env::var("PORT").expect("PORT must be set");
         let url = std::env::var("URL").expect("URL must be set");
+        let max_batch_size = std::env::var("MAX_BATCH_SIZE")
+            .ok()
+            .map(|v| v.parse().expect("MAX_BATCH_SIZE must be usize type"))
+            .unwrap_or(1000);
+        let max_payload_bytes = std::env::var("MAX_PAYLOAD_BYTES")
+            .ok()
+            .map(|v| v.parse().expect("MAX_PAYLOAD_BYTES must be usize type"))
+            .unwrap_or(64 * 1024 * 1024);
+        let max_import_size = std::env::var("MAX_IMPORT_SIZE")
+            .ok()
+            .map(|v| v.parse().expect("MAX_IMPORT_SIZE must be usize type"))
+            .unwrap_or(100_000);
+        let trash_retention_secs = std::env::var("TRASH_RETENTION_SECS")
+            .ok()
+            .map(|v| v.parse().expect("TRASH_RETENTION_SECS must be u64 type"))
+            .unwrap_or(7 * 24 * 60 * 60);
+        let nl_rules_path = std::env::var("NL_RULES_PATH").ok().filter(|v| !v.is_empty());
+        let nl_rules = match &nl_rules_path {
+            Some(path) => crate::query::rules::load_rules(path).unwrap_or_else(|e| panic!("NL_RULES_PATH: {}", e)),
+            None => Vec::new(),
+        };
+
+        let event_log_size = std::env::var("EVENT_LOG_SIZE")
+            .ok()
+            .map(|v| v.parse().expect("EVENT_LOG_SIZE must be usize type"))
+            .unwrap_or(1000);
+        let webhook_max_attempts = std::env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .map(|v| v.parse().expect("WEBHOOK_MAX_ATTEMPTS must be u32 type"))
+            .unwrap_or(5);
+        let webhook_retry_base_ms = std::env::var("WEBHOOK_RETRY_BASE_MS")
+            .ok()
+            .map(|v| v.parse().expect("WEBHOOK_RETRY_BASE_MS must be u64 type"))
+            .unwrap_or(1000);
+        let webhook_allow_private = std::env::var("WEBHOOK_ALLOW_PRIVATE")
+            .ok()
+            .map(|v| v.parse().expect("WEBHOOK_ALLOW_PRIVATE must be true or false"))
+            .unwrap_or(false);
+        let snapshot_path = std::env::var("SNAPSHOT_PATH").ok().filter(|v| !v.is_empty());
+        let snapshot_interval_secs = std::env::var("SNAPSHOT_INTERVAL_SECS")
+            .ok()
+            .map(|v| v.parse().expect("SNAPSHOT_INTERVAL_SECS must be u64 type"))
+            .unwrap_or(300);
 
-        let db = TempDatabase::new();
+        let wal_path = std::env::var("WAL_PATH").ok().filter(|v| !v.is_empty());
 
+        let snapshot = match &snapshot_path {
+            Some(path) => match crate::config::snapshot::load_snapshot(path) {
+                Ok(snapshot) => snapshot,
+                Err(crate::config::snapshot::LoadError::Unreadable(e)) => panic!("SNAPSHOT_PATH: {}", e),
+                Err(crate::config::snapshot::LoadError::Corrupt(e)) => {
+                    // The log only holds changes on top of this snapshot, so
+                    // it is set aside with it and the service starts empty.
+                    println!("Ignoring {}", e);
+                    let wal_paths = wal_path.iter().flat_map(|path| [crate::config::wal::rotated_path(path), path.clone()]);
+                    for path in std::iter::once(path.clone()).chain(wal_paths) {
+                        match crate::config::snapshot::set_aside(&path) {
+                            Ok(target) => println!("Moved {} to {}", path, target),
+                            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                            Err(e) => panic!("cannot move {} aside: {}", path, e),
+                        }
+                    }
+                    None
+                }
+            },
+            None => None,
+        };
+        let (mut db, mut collections, mut webhooks) = match snapshot {
+            Some(snapshot) => {
+                println!("Restored {} strings and {} webhooks from snapshot", snapshot.string_count(), snapshot.webhooks.len());
+                (snapshot.db, snapshot.collections, snapshot.webhooks)
+            },
+            None => (TempDatabase::new(), std::collections::HashMap::new(), Vec::new()),
+        };
+
+        let wal = wal_path.map(|path| {
+            // Compaction folds the log into the snapshot, so there must be one.
+            if snapshot_path.is_none() {
+                panic!("WAL_PATH requires SNAPSHOT_PATH to be set");
+            }
+            // Entries rotated out for a snapshot that never got written come first.
+            let mut replayed = 0;
+            for log in [crate::config::wal::rotated_path(&path), path.clone()] {
+                replayed += crate::config::wal::replay_wal(&log, &mut db, &mut collections, &mut webhooks).unwrap_or_else(|e| panic!("WAL_PATH: {}", e));
+            }
+            if replayed > 0 {
+                println!("Replayed {} operations from the write-ahead log", replayed);
+            }
+            crate::config::wal::Wal::open(&path).unwrap_or_else(|e| panic!("WAL_PATH: {}", e))
+        });
+
+        let webhook_registry = crate::config::webhooks::WebhookRegistry::new(
+            webhook_max_attempts.max(1),
+            std::time::Duration::from_millis(webhook_retry_base_ms),
+            webhook_allow_private,
+        );
+        for webhook in webhooks {
+            webhook_registry.register(webhook);
+        }
 
         Config {
             port,
             url,
-            db: std::sync::Mutex::new(db),
+            max_batch_size,
+            max_payload_bytes,
+            max_import_size,
+            trash_retention_secs,
+            nl_rules_path,
+            nl_rules: std::sync::RwLock::new(std::sync::Arc::new(nl_rules)),
+            snapshot_path,
+            snapshot_interval_secs,
+            wal,
+            snapshot_lock: std::sync::Mutex::new(()),
+            events: crate::config::events::EventLog::new(event_log_size),
+            webhooks: webhook_registry,
+            db: std::sync::Arc::new(std::sync::Mutex::new(db)),
+            collections: std::sync::Mutex::new(
+                collections
+                    .into_iter()
+                    .map(|(name, db)| (name, std::sync::Arc::new(std::sync::Mutex::new(db))))
+                    .collect(),
+            ),
         }
     }
 }
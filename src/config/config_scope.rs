@@ -1,9 +1,41 @@
+use crate::config::api_version::ApiVersion;
 use crate::routes::healthz::check_health;
 // use crate::routes::me::me;
-use crate::routes::strings::{process_string, get_string_details, delete_string, get_strings_filtered, filter_by_natural_language};
-use actix_web::web;
+use crate::routes::events::string_events;
+use crate::routes::collections::{delete_collection, get_collection, list_collections};
+use crate::routes::socket::analyse_socket;
+use crate::routes::stats::{get_stats, get_stats_timeline};
+use crate::routes::strings::{process_string, process_string_batch, get_string_details, delete_string, delete_strings_filtered, get_strings_filtered, filter_by_natural_language, suggest_natural_language, update_string_context};
+use crate::routes::transfer::{export_strings, import_strings};
+use crate::routes::trash::{list_trash, restore_string};
+use crate::routes::webhooks::{create_webhook, delete_webhook, list_deliveries, list_webhooks, test_webhook};
+use actix_web::{Scope, web};
 
 pub fn config(conf: &mut web::ServiceConfig) {
-    let scope = web::scope("/api/v1").service(check_health).service(process_string).service(filter_by_natural_language).service(get_strings_filtered).service(get_string_details).service(delete_string);
-    conf.service(scope);
+    conf.service(api_scope("/api/v1", ApiVersion::V1));
+    // v2 shares every handler with v1; only the behaviour keyed off `ApiVersion` differs.
+    conf.service(api_scope("/api/v2", ApiVersion::V2));
+}
+
+fn api_scope(path: &str, version: ApiVersion) -> Scope {
+    // Collection-level routes must be registered ahead of the nested scope,
+    // which would otherwise swallow `/collections/{collection}` itself.
+    let scope = web::scope(path)
+        .app_data(version)
+        .service(check_health)
+        .service(list_collections)
+        .service(get_collection)
+        .service(delete_collection)
+        .service(create_webhook)
+        .service(list_webhooks)
+        .service(delete_webhook)
+        .service(list_deliveries)
+        .service(test_webhook)
+        .service(string_services(web::scope("/collections/{collection}")));
+    string_services(scope)
+}
+
+/// Registers the strings, events, live analysis, trash, stats and export/import routes, shared by the global store and every collection.
+fn string_services(scope: Scope) -> Scope {
+    scope.service(process_string_batch).service(process_string).service(suggest_natural_language).service(filter_by_natural_language).service(get_strings_filtered).service(string_events).service(get_string_details).service(update_string_context).service(delete_string).service(delete_strings_filtered).service(list_trash).service(restore_string).service(get_stats_timeline).service(get_stats).service(export_strings).service(import_strings).service(analyse_socket)
 }
@@ -0,0 +1,195 @@
+use crate::config::config::AnalysisResult;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+/// Live subscribers that fall this far behind catch up from the event log.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Deleted,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A string added to or deleted from a store. `collection` is `None` for the global store.
+#[derive(Debug)]
+pub struct StoreEvent {
+    pub id: u64,
+    pub kind: EventKind,
+    pub collection: Option<String>,
+    pub result: AnalysisResult,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The most recent events, kept so clients can resume a change feed, plus a
+/// channel that delivers new ones as they happen.
+#[derive(Debug)]
+pub struct EventLog {
+    capacity: usize,
+    recent: std::sync::Mutex<RecentEvents>,
+    sender: broadcast::Sender<Arc<StoreEvent>>,
+    /// Every event, in order, for the webhook dispatcher. Unlike the broadcast
+    /// channel it never drops events when its consumer falls behind.
+    queue: mpsc::UnboundedSender<Arc<StoreEvent>>,
+    queue_receiver: std::sync::Mutex<Option<mpsc::UnboundedReceiver<Arc<StoreEvent>>>>,
+}
+
+#[derive(Debug)]
+struct RecentEvents {
+    next_id: u64,
+    events: VecDeque<Arc<StoreEvent>>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> EventLog {
+        let (queue, queue_receiver) = mpsc::unbounded_channel();
+        EventLog {
+            capacity,
+            recent: std::sync::Mutex::new(RecentEvents {
+                // Ids start from the boot time in microseconds, so an id handed
+                // out before a restart is never mistaken for a newer one.
+                next_id: u64::try_from(chrono::Utc::now().timestamp_micros()).unwrap_or(0).max(1),
+                events: VecDeque::with_capacity(capacity),
+            }),
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            queue,
+            queue_receiver: std::sync::Mutex::new(Some(queue_receiver)),
+        }
+    }
+
+    /// Records an event and sends it to every subscriber.
+    pub fn publish(&self, kind: EventKind, collection: Option<String>, result: AnalysisResult) {
+        let mut recent = self.recent.lock().expect("event log mutex poisoned");
+        let event = Arc::new(StoreEvent {
+            id: recent.next_id,
+            kind,
+            collection,
+            result,
+            at: chrono::Utc::now(),
+        });
+        recent.next_id += 1;
+
+        if self.capacity > 0 {
+            if recent.events.len() == self.capacity {
+                recent.events.pop_front();
+            }
+            recent.events.push_back(event.clone());
+        }
+        // Sent under the lock so subscribers see events in id order. Having
+        // no subscribers is not an error.
+        let _ = self.queue.send(event.clone());
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<StoreEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Takes the lossless queue of every event published from now on. It has
+    /// a single consumer, so only the first call gets it.
+    pub fn take_queue(&self) -> Option<mpsc::UnboundedReceiver<Arc<StoreEvent>>> {
+        self.queue_receiver.lock().expect("event queue mutex poisoned").take()
+    }
+
+    /// Retained events after `last_id`, and whether some events after it
+    /// have already been dropped from the log. An id this log has not handed
+    /// out yet, such as one from before a restart, also counts as missing
+    /// events, and every retained event is returned.
+    pub fn since(&self, last_id: u64) -> (Vec<Arc<StoreEvent>>, bool) {
+        let recent = self.recent.lock().expect("event log mutex poisoned");
+        if last_id >= recent.next_id {
+            return (recent.events.iter().cloned().collect(), true);
+        }
+
+        let first_retained = recent.events.front().map(|event| event.id).unwrap_or(recent.next_id);
+        let events = recent.events.iter().filter(|event| event.id > last_id).cloned().collect();
+        (events, last_id.saturating_add(1) < first_retained)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::strings::analyse_string;
+
+    fn log_with(capacity: usize, count: usize) -> (EventLog, Vec<u64>) {
+        let log = EventLog::new(capacity);
+        for i in 0..count {
+            log.publish(EventKind::Created, None, analyse_string(format!("string {}", i)));
+        }
+        let (events, _) = log.since(0);
+        let ids = events.iter().map(|event| event.id).collect();
+        (log, ids)
+    }
+
+    fn ids(events: &[Arc<StoreEvent>]) -> Vec<u64> {
+        events.iter().map(|event| event.id).collect()
+    }
+
+    #[test]
+    fn returns_events_after_the_last_seen_id() {
+        let (log, all) = log_with(10, 4);
+        assert_eq!(all.windows(2).filter(|pair| pair[1] != pair[0] + 1).count(), 0);
+
+        let (events, missed) = log.since(all[1]);
+        assert_eq!(ids(&events), all[2..].to_vec());
+        assert!(!missed);
+
+        let (events, missed) = log.since(all[3]);
+        assert!(events.is_empty());
+        assert!(!missed);
+    }
+
+    #[test]
+    fn reports_events_dropped_from_the_log() {
+        let (log, retained) = log_with(2, 5);
+        assert_eq!(retained.len(), 2);
+
+        // The event just before the oldest retained one is still covered.
+        let (events, missed) = log.since(retained[0] - 1);
+        assert_eq!(ids(&events), retained);
+        assert!(!missed);
+
+        let (events, missed) = log.since(retained[0] - 2);
+        assert_eq!(ids(&events), retained);
+        assert!(missed);
+    }
+
+    #[test]
+    fn ids_from_another_boot_are_missed() {
+        let (log, all) = log_with(10, 3);
+
+        // An id from before a restart is older than anything handed out since.
+        let (events, missed) = log.since(1);
+        assert_eq!(ids(&events), all);
+        assert!(missed);
+
+        let (events, missed) = log.since(all[2] + 1);
+        assert_eq!(ids(&events), all);
+        assert!(missed);
+
+        let (_, missed) = log.since(u64::MAX);
+        assert!(missed);
+    }
+
+    #[test]
+    fn an_empty_log_only_misses_unknown_ids() {
+        let log = EventLog::new(10);
+        let (_, missed) = log.since(u64::MAX);
+        assert!(missed);
+        let (events, missed) = log.since(0);
+        assert!(events.is_empty());
+        assert!(missed);
+    }
+}
@@ -1,2 +1,8 @@
+pub mod api_version;
+#[allow(clippy::module_inception)]
 pub mod config;
 pub mod config_scope;
+pub mod events;
+pub mod snapshot;
+pub mod wal;
+pub mod webhooks;
@@ -0,0 +1,174 @@
+use crate::config::config::{Config, TempDatabase};
+use crate::config::webhooks::Webhook;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// On-disk copy of the global store, every collection and the registered webhooks.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub db: TempDatabase,
+    #[serde(default)]
+    pub collections: HashMap<String, TempDatabase>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+impl Snapshot {
+    /// Copies the stores out of `config`, holding each lock only while cloning it.
+    pub fn capture(config: &Config) -> Snapshot {
+        let db = config.db.lock().expect("db mutex poisoned").clone();
+        let collections = {
+            let collections = config.collections.lock().expect("collections mutex poisoned");
+            collections
+                .iter()
+                .map(|(name, db)| (name.clone(), db.lock().expect("db mutex poisoned").clone()))
+                .collect()
+        };
+
+        Snapshot {
+            db,
+            collections,
+            webhooks: config.webhooks.list(),
+        }
+    }
+
+    /// Number of stored strings across the global store and collections.
+    pub fn string_count(&self) -> usize {
+        self.db.processed_results.len() + self.collections.values().map(|db| db.processed_results.len()).sum::<usize>()
+    }
+}
+
+/// Why a snapshot could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file exists but could not be read.
+    Unreadable(String),
+    /// The file was read but does not hold a valid snapshot.
+    Corrupt(String),
+}
+
+/// Reads the snapshot at `path`, or `None` when there is none yet.
+pub fn load_snapshot(path: &str) -> Result<Option<Snapshot>, LoadError> {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(None);
+        },
+        Err(e) => {
+            return Err(LoadError::Unreadable(format!("cannot read {}: {}", path, e)));
+        }
+    };
+
+    serde_json::from_slice(&contents).map(Some).map_err(|e| LoadError::Corrupt(format!("invalid snapshot {}: {}", path, e)))
+}
+
+/// Renames `path` to `<path>.corrupt-<unix time>` so it is kept for
+/// inspection but no longer loaded, returning the new name.
+pub fn set_aside(path: &str) -> std::io::Result<String> {
+    let target = format!("{}.corrupt-{}", path, chrono::Utc::now().timestamp());
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Writes the snapshot next to `path` and renames it into place, so a crash
+/// mid-write leaves the previous snapshot intact.
+pub fn write_snapshot(path: &str, snapshot: &Snapshot) -> Result<(), String> {
+    let tmp_path = format!("{}.tmp", path);
+    let write = || -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+        serde_json::to_writer(&mut file, snapshot)?;
+        file.flush()?;
+        file.get_ref().sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+        // The rename only survives a crash once the directory entry is on disk.
+        crate::config::wal::sync_parent_dir(path)
+    };
+
+    write().map_err(|e| format!("cannot write {}: {}", path, e))
+}
+
+/// Captures and writes the current state when `SNAPSHOT_PATH` is set, then
+/// deletes the write-ahead log entries the snapshot now covers.
+pub fn save_snapshot(config: &Config) {
+    let Some(path) = &config.snapshot_path else {
+        return;
+    };
+
+    // The periodic task and the shutdown hook share `path.tmp`.
+    let _saving = config.snapshot_lock.lock().expect("snapshot mutex poisoned");
+    // Writers wait only while the state is cloned and the log rotated, so
+    // the rotated entries are exactly the ones the snapshot covers.
+    let snapshot = {
+        let mut log = config.wal_writer();
+        let snapshot = Snapshot::capture(config);
+        if let Err(e) = log.rotate() {
+            println!("Skipping snapshot, failed to rotate the write-ahead log: {}", e);
+            return;
+        }
+        snapshot
+    };
+
+    match write_snapshot(path, &snapshot) {
+        Ok(()) => {
+            println!("Wrote snapshot of {} strings to {}", snapshot.string_count(), path);
+            if let Some(wal) = &config.wal
+                && let Err(e) = wal.remove_rotated()
+            {
+                println!("Failed to compact the write-ahead log: {}", e);
+            }
+        },
+        Err(e) => {
+            println!("Failed to write snapshot: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::strings::analyse_string;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("snapshot-test-{}-{}.json", name, std::process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let path = temp_path("round-trip");
+        let mut db = TempDatabase::new();
+        let result = analyse_string("level".to_string());
+        db.processed_strings_hash.push(result.sha256_hash.clone());
+        db.processed_results.push(result);
+        let snapshot = Snapshot {
+            db,
+            collections: HashMap::new(),
+            webhooks: Vec::new(),
+        };
+
+        write_snapshot(&path, &snapshot).unwrap();
+        let loaded = load_snapshot(&path).unwrap().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.string_count(), 1);
+        assert_eq!(loaded.db.processed_results[0].word, "level");
+    }
+
+    #[test]
+    fn a_missing_snapshot_loads_nothing() {
+        assert!(load_snapshot(&temp_path("missing")).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_truncated_snapshot_is_corrupt() {
+        let path = temp_path("truncated");
+        std::fs::write(&path, "{\"db\":{\"processed_strings_hash\":[").unwrap();
+
+        let loaded = load_snapshot(&path);
+        let target = set_aside(&path).unwrap();
+        std::fs::remove_file(&target).unwrap();
+
+        assert!(matches!(loaded, Err(LoadError::Corrupt(_))));
+        assert!(target.starts_with(&format!("{}.corrupt-", path)));
+    }
+}
@@ -0,0 +1,503 @@
+use crate::config::config::{AnalysisResult, Config, TempDatabase};
+use crate::config::webhooks::Webhook;
+use crate::error::ApiError;
+use actix_web::web;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// One change to a store, as recorded in the write-ahead log. `collection`
+/// is `None` for the global store.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WalOp {
+    Insert {
+        collection: Option<String>,
+        result: AnalysisResult,
+    },
+    Delete {
+        collection: Option<String>,
+        hash: String,
+        deleted_at: chrono::DateTime<chrono::Utc>,
+    },
+    Restore {
+        collection: Option<String>,
+        hash: String,
+    },
+    /// Tags and metadata of a stored string after an edit.
+    Update {
+        collection: Option<String>,
+        hash: String,
+        tags: Vec<String>,
+        metadata: serde_json::Map<String, serde_json::Value>,
+    },
+    PurgeTrash {
+        collection: Option<String>,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    },
+    /// Logged ahead of the first change stored into a new collection.
+    CreateCollection {
+        collection: String,
+    },
+    DropCollection {
+        collection: String,
+    },
+    RegisterWebhook {
+        webhook: Webhook,
+    },
+    RemoveWebhook {
+        id: String,
+    },
+}
+
+/// Append-only log of changes made since the last snapshot.
+///
+/// A snapshot rotates the log to [`rotated_path`] while it captures the
+/// stores, and deletes the rotated log once the snapshot is on disk. Until
+/// then, both are replayed on startup, the rotated one first.
+#[derive(Debug)]
+pub struct Wal {
+    path: String,
+    file: std::sync::Mutex<std::fs::File>,
+    // A second handle to the same file, so flushing to disk needs no lock.
+    sync: std::sync::Mutex<Arc<std::fs::File>>,
+}
+
+/// Where the log is moved while a snapshot covering it is being written.
+pub fn rotated_path(path: &str) -> String {
+    format!("{}.old", path)
+}
+
+impl Wal {
+    pub fn open(path: &str) -> Result<Wal, String> {
+        let file = open_log(path).map_err(|e| format!("cannot open {}: {}", path, e))?;
+        let sync = file.try_clone().map_err(|e| format!("cannot open {}: {}", path, e))?;
+
+        Ok(Wal {
+            path: path.to_string(),
+            file: std::sync::Mutex::new(file),
+            sync: std::sync::Mutex::new(Arc::new(sync)),
+        })
+    }
+
+    /// Deletes the rotated log once a snapshot covers its entries.
+    pub fn remove_rotated(&self) -> std::io::Result<()> {
+        match std::fs::remove_file(rotated_path(&self.path)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Opens the log for appending. A torn last entry left by a crash is cut
+/// off, so new entries do not run on from it.
+fn open_log(path: &str) -> std::io::Result<std::fs::File> {
+    let file = std::fs::OpenOptions::new().create(true).append(true).read(true).open(path)?;
+    let contents = std::fs::read(path)?;
+    if !contents.is_empty() && !contents.ends_with(b"\n") {
+        let complete = contents.iter().rposition(|b| *b == b'\n').map_or(0, |end| end + 1);
+        file.set_len(complete as u64)?;
+        file.sync_all()?;
+    }
+    Ok(file)
+}
+
+/// Flushes the directory holding `path`, so a file created or renamed in it
+/// survives a crash.
+pub(crate) fn sync_parent_dir(path: &str) -> std::io::Result<()> {
+    let dir = match std::path::Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Exclusive access to the log. Hold it from appending an operation until
+/// the change is applied to the store, so a compaction cannot run in between
+/// and drop the entry. Take it before any store lock.
+pub struct WalWriter<'a> {
+    wal: Option<&'a Wal>,
+    file: Option<std::sync::MutexGuard<'a, std::fs::File>>,
+    prefix: Vec<WalOp>,
+}
+
+impl Config {
+    /// Locks the write-ahead log; a no-op writer when `WAL_PATH` is unset.
+    pub fn wal_writer(&self) -> WalWriter<'_> {
+        WalWriter {
+            wal: self.wal.as_ref(),
+            file: self.wal.as_ref().map(|wal| wal.file.lock().expect("wal mutex poisoned")),
+            prefix: Vec::new(),
+        }
+    }
+
+    /// Flushes appended entries to disk on the blocking thread pool. Call it
+    /// after releasing the writer and the store locks, and before replying,
+    /// so concurrent writers share one fsync instead of queueing behind it.
+    pub async fn wal_sync(&self) -> Result<(), ApiError> {
+        let Some(wal) = &self.wal else {
+            return Ok(());
+        };
+
+        let file = wal.sync.lock().expect("wal mutex poisoned").clone();
+        web::block(move || file.sync_data())
+            .await
+            .map_err(|_| ApiError::Internal("Failed to flush the write-ahead log".to_string()))?
+            .map_err(|e| ApiError::Internal(format!("Could not write to the write-ahead log: {}", e)))
+    }
+}
+
+impl WalWriter<'_> {
+    /// Appends `ops` to the log. They reach the disk on the next
+    /// [`Config::wal_sync`].
+    pub fn append(&mut self, ops: &[WalOp]) -> Result<(), ApiError> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        if ops.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = Vec::new();
+        for op in self.prefix.iter().chain(ops) {
+            serde_json::to_writer(&mut lines, op).map_err(|e| ApiError::Internal(format!("Could not encode log entry: {}", e)))?;
+            lines.push(b'\n');
+        }
+
+        file.write_all(&lines)
+            .map_err(|e| ApiError::Internal(format!("Could not write to the write-ahead log: {}", e)))?;
+        self.prefix.clear();
+        Ok(())
+    }
+
+    /// Queues `op` to be written in front of the next non-empty append, so
+    /// it is only logged along with a change that depends on it.
+    pub fn prepend(&mut self, op: WalOp) {
+        self.prefix.push(op);
+    }
+
+    /// Moves the log's entries to [`rotated_path`] and starts an empty log.
+    /// If an earlier rotated log is still there, because the snapshot meant
+    /// to cover it failed, the entries are appended to it instead.
+    pub fn rotate(&mut self) -> std::io::Result<()> {
+        let (Some(wal), Some(file)) = (self.wal, self.file.as_mut()) else {
+            return Ok(());
+        };
+        let rotated = rotated_path(&wal.path);
+
+        if std::path::Path::new(&rotated).exists() {
+            let mut old = std::fs::OpenOptions::new().append(true).open(&rotated)?;
+            old.write_all(&std::fs::read(&wal.path)?)?;
+            old.sync_data()?;
+            file.set_len(0)?;
+            return file.sync_all();
+        }
+
+        file.sync_data()?;
+        std::fs::rename(&wal.path, &rotated)?;
+        let fresh = open_log(&wal.path)?;
+        sync_parent_dir(&wal.path)?;
+        *wal.sync.lock().expect("wal mutex poisoned") = Arc::new(fresh.try_clone()?);
+        **file = fresh;
+        Ok(())
+    }
+}
+
+/// Re-applies the logged operations on top of the stores and webhooks loaded
+/// from the snapshot, returning how many were replayed. A torn final line,
+/// left by a crash mid-append, is ignored.
+pub fn replay_wal(path: &str, db: &mut TempDatabase, collections: &mut HashMap<String, TempDatabase>, webhooks: &mut Vec<Webhook>) -> Result<usize, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(0);
+        },
+        Err(e) => {
+            return Err(format!("cannot read {}: {}", path, e));
+        }
+    };
+
+    let complete = contents.ends_with('\n');
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut replayed = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let op: WalOp = match serde_json::from_str(line) {
+            Ok(op) => op,
+            Err(_) if index + 1 == lines.len() && !complete => {
+                println!("Ignoring incomplete last entry of {}", path);
+                break;
+            },
+            Err(e) => {
+                return Err(format!("invalid entry on line {} of {}: {}", index + 1, path, e));
+            }
+        };
+
+        match op {
+            WalOp::Insert { collection, result } => {
+                if let Some(store) = logged_store(db, collections, collection) {
+                    store.upsert(result);
+                }
+            },
+            WalOp::Delete { collection, hash, deleted_at } => {
+                if let Some(store) = logged_store(db, collections, collection) {
+                    store.soft_delete(&hash, deleted_at);
+                }
+            },
+            WalOp::Restore { collection, hash } => {
+                if let Some(store) = logged_store(db, collections, collection) {
+                    let _ = store.restore(&hash);
+                }
+            },
+            WalOp::Update { collection, hash, tags, metadata } => {
+                if let Some(result) = logged_store(db, collections, collection).and_then(|store| store.find_mut(&hash)) {
+                    result.tags = tags;
+                    result.metadata = metadata;
+                }
+            },
+            WalOp::PurgeTrash { collection, cutoff } => {
+                if let Some(store) = logged_store(db, collections, collection) {
+                    store.purge_trash(cutoff);
+                }
+            },
+            WalOp::CreateCollection { collection } => {
+                collections.entry(collection).or_insert_with(TempDatabase::new);
+            },
+            WalOp::DropCollection { collection } => {
+                collections.remove(&collection);
+            },
+            WalOp::RegisterWebhook { webhook } => {
+                webhooks.retain(|hook| hook.id != webhook.id);
+                webhooks.push(webhook);
+            },
+            WalOp::RemoveWebhook { id } => {
+                webhooks.retain(|hook| hook.id != id);
+            }
+        }
+        replayed += 1;
+    }
+
+    Ok(replayed)
+}
+
+/// The store a logged operation applies to, if it still exists.
+fn logged_store<'a>(db: &'a mut TempDatabase, collections: &'a mut HashMap<String, TempDatabase>, collection: Option<String>) -> Option<&'a mut TempDatabase> {
+    match collection {
+        Some(name) => collections.get_mut(&name),
+        None => Some(db),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::strings::analyse_string;
+
+    /// Writes `ops` as a log file, followed by `tail` verbatim.
+    fn write_log(name: &str, ops: &[WalOp], tail: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("wal-test-{}-{}.log", name, std::process::id()));
+        let mut contents = String::new();
+        for op in ops {
+            contents.push_str(&serde_json::to_string(op).unwrap());
+            contents.push('\n');
+        }
+        contents.push_str(tail);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    struct Replayed {
+        count: usize,
+        db: TempDatabase,
+        collections: HashMap<String, TempDatabase>,
+        webhooks: Vec<Webhook>,
+    }
+
+    fn replay(path: &std::path::Path) -> Result<Replayed, String> {
+        let mut db = TempDatabase::new();
+        let mut collections = HashMap::new();
+        let mut webhooks = Vec::new();
+        let count = replay_wal(path.to_str().unwrap(), &mut db, &mut collections, &mut webhooks)?;
+        std::fs::remove_file(path).unwrap();
+        Ok(Replayed { count, db, collections, webhooks })
+    }
+
+    fn webhook(id: &str) -> Webhook {
+        Webhook {
+            id: id.to_string(),
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![crate::config::events::EventKind::Created],
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn replays_every_operation_in_order() {
+        let kept = analyse_string("kept".to_string());
+        let restored = analyse_string("restored".to_string());
+        let purged = analyse_string("purged".to_string());
+        let deleted_at = chrono::Utc::now() - chrono::TimeDelta::days(2);
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("source".to_string(), serde_json::json!("test"));
+
+        let path = write_log(
+            "ops",
+            &[
+                WalOp::Insert { collection: None, result: kept.clone() },
+                WalOp::Insert { collection: None, result: restored.clone() },
+                WalOp::Insert { collection: None, result: purged.clone() },
+                WalOp::Delete { collection: None, hash: restored.sha256_hash.clone(), deleted_at },
+                WalOp::Delete { collection: None, hash: purged.sha256_hash.clone(), deleted_at },
+                WalOp::Restore { collection: None, hash: restored.sha256_hash.clone() },
+                WalOp::PurgeTrash { collection: None, cutoff: chrono::Utc::now() },
+                WalOp::Update {
+                    collection: None,
+                    hash: kept.sha256_hash.clone(),
+                    tags: vec!["a".to_string()],
+                    metadata: metadata.clone(),
+                },
+                WalOp::CreateCollection { collection: "team".to_string() },
+                WalOp::Insert { collection: Some("team".to_string()), result: kept.clone() },
+                WalOp::CreateCollection { collection: "gone".to_string() },
+                WalOp::Insert { collection: Some("gone".to_string()), result: kept.clone() },
+                WalOp::DropCollection { collection: "gone".to_string() },
+            ],
+            "",
+        );
+
+        let Replayed { count, mut db, collections, .. } = replay(&path).unwrap();
+        assert_eq!(count, 13);
+        assert_eq!(db.processed_strings_hash, vec![kept.sha256_hash.clone(), restored.sha256_hash.clone()]);
+        assert!(db.trash.is_empty());
+        let edited = db.find_mut(&kept.sha256_hash).unwrap();
+        assert_eq!(edited.tags, vec!["a".to_string()]);
+        assert_eq!(edited.metadata, metadata);
+        assert_eq!(collections.keys().collect::<Vec<_>>(), vec!["team"]);
+        assert_eq!(collections["team"].processed_results.len(), 1);
+    }
+
+    #[test]
+    fn ignores_inserts_into_a_dropped_collection() {
+        let first = analyse_string("first".to_string());
+        let orphan = analyse_string("orphan".to_string());
+        let path = write_log(
+            "dropped",
+            &[
+                WalOp::CreateCollection { collection: "team".to_string() },
+                WalOp::Insert { collection: Some("team".to_string()), result: first },
+                WalOp::DropCollection { collection: "team".to_string() },
+                WalOp::Insert { collection: Some("team".to_string()), result: orphan },
+            ],
+            "",
+        );
+
+        let Replayed { collections, .. } = replay(&path).unwrap();
+        assert!(collections.is_empty());
+    }
+
+    #[test]
+    fn ignores_a_torn_last_line() {
+        let result = analyse_string("hello".to_string());
+        let path = write_log("torn", &[WalOp::Insert { collection: None, result }], "{\"op\":\"insert\",\"collec");
+
+        let Replayed { count, db, .. } = replay(&path).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(db.processed_results.len(), 1);
+    }
+
+    #[test]
+    fn rejects_a_corrupt_line_before_the_end() {
+        let result = analyse_string("hello".to_string());
+        let path = write_log("corrupt", &[], "not json\n");
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.push_str(&serde_json::to_string(&WalOp::Insert { collection: None, result }).unwrap());
+        contents.push('\n');
+        std::fs::write(&path, contents).unwrap();
+
+        assert!(replay(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn a_missing_log_replays_nothing() {
+        let path = std::env::temp_dir().join(format!("wal-test-missing-{}.log", std::process::id()));
+        let mut db = TempDatabase::new();
+        let mut collections = HashMap::new();
+        assert_eq!(replay_wal(path.to_str().unwrap(), &mut db, &mut collections, &mut Vec::new()), Ok(0));
+    }
+
+    fn writer(wal: &Wal) -> WalWriter<'_> {
+        WalWriter {
+            wal: Some(wal),
+            file: Some(wal.file.lock().unwrap()),
+            prefix: Vec::new(),
+        }
+    }
+
+    fn insert(value: &str) -> WalOp {
+        WalOp::Insert {
+            collection: None,
+            result: analyse_string(value.to_string()),
+        }
+    }
+
+    fn line_count(path: &str) -> usize {
+        std::fs::read_to_string(path).map(|contents| contents.lines().count()).unwrap_or(0)
+    }
+
+    #[test]
+    fn rotation_moves_entries_aside_until_removed() {
+        let path = std::env::temp_dir().join(format!("wal-test-rotate-{}.log", std::process::id())).to_string_lossy().into_owned();
+        let rotated = rotated_path(&path);
+        let wal = Wal::open(&path).unwrap();
+
+        writer(&wal).append(&[insert("one")]).unwrap();
+        writer(&wal).rotate().unwrap();
+        writer(&wal).append(&[insert("two")]).unwrap();
+        assert_eq!((line_count(&rotated), line_count(&path)), (1, 1));
+
+        // A second rotation before the first snapshot landed keeps both.
+        writer(&wal).rotate().unwrap();
+        assert_eq!((line_count(&rotated), line_count(&path)), (2, 0));
+
+        wal.remove_rotated().unwrap();
+        assert!(!std::path::Path::new(&rotated).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn opening_cuts_off_a_torn_last_line() {
+        let path = write_log("reopen", &[insert("kept")], "{\"op\":\"ins");
+        let path = path.to_string_lossy().into_owned();
+
+        let wal = Wal::open(&path).unwrap();
+        writer(&wal).append(&[insert("next")]).unwrap();
+        drop(wal);
+
+        let mut db = TempDatabase::new();
+        let replayed = replay_wal(&path, &mut db, &mut HashMap::new(), &mut Vec::new()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replayed, 2);
+    }
+
+    #[test]
+    fn replays_webhook_registrations() {
+        let path = write_log(
+            "webhooks",
+            &[
+                WalOp::RegisterWebhook { webhook: webhook("kept") },
+                WalOp::RegisterWebhook { webhook: webhook("removed") },
+                WalOp::RemoveWebhook { id: "removed".to_string() },
+            ],
+            "",
+        );
+
+        let Replayed { count, webhooks, .. } = replay(&path).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(webhooks.iter().map(|hook| hook.id.as_str()).collect::<Vec<_>>(), vec!["kept"]);
+    }
+}
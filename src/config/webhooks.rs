@@ -0,0 +1,218 @@
+use crate::config::events::EventKind;
+use std::collections::VecDeque;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Deliveries kept for the delivery log, across all webhooks.
+const MAX_DELIVERY_LOG: usize = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<EventKind>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// One try at sending a delivery; `status_code` is absent when no response arrived.
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct Delivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    pub status: DeliveryStatus,
+    pub attempts: Vec<DeliveryAttempt>,
+    pub next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Registered webhooks and the log of recent deliveries to them.
+#[derive(Debug)]
+pub struct WebhookRegistry {
+    pub max_attempts: u32,
+    pub retry_base: std::time::Duration,
+    /// Whether webhooks may target loopback, private and other non-public addresses.
+    pub allow_private: bool,
+    pub client: reqwest::Client,
+    hooks: std::sync::Mutex<Vec<Webhook>>,
+    deliveries: std::sync::Mutex<VecDeque<Delivery>>,
+}
+
+impl WebhookRegistry {
+    pub fn new(max_attempts: u32, retry_base: std::time::Duration, allow_private: bool) -> WebhookRegistry {
+        // Redirects are not followed, so a public receiver cannot bounce a
+        // delivery to an internal address.
+        let mut client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+        if !allow_private {
+            client = client.dns_resolver(std::sync::Arc::new(PublicResolver));
+        }
+
+        WebhookRegistry {
+            max_attempts,
+            retry_base,
+            allow_private,
+            client: client.build().expect("webhook HTTP client"),
+            hooks: std::sync::Mutex::new(Vec::new()),
+            deliveries: std::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn register(&self, hook: Webhook) {
+        self.hooks.lock().expect("webhooks mutex poisoned").push(hook);
+    }
+
+    pub fn list(&self) -> Vec<Webhook> {
+        self.hooks.lock().expect("webhooks mutex poisoned").clone()
+    }
+
+    pub fn find(&self, id: &str) -> Option<Webhook> {
+        self.hooks.lock().expect("webhooks mutex poisoned").iter().find(|hook| hook.id == id).cloned()
+    }
+
+    /// Removes a webhook and its delivery log. Returns `false` when it does not exist.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut hooks = self.hooks.lock().expect("webhooks mutex poisoned");
+        let Some(index) = hooks.iter().position(|hook| hook.id == id) else {
+            return false;
+        };
+        hooks.remove(index);
+        self.deliveries.lock().expect("deliveries mutex poisoned").retain(|delivery| delivery.webhook_id != id);
+        return true;
+    }
+
+    /// Webhooks subscribed to `kind`.
+    pub fn subscribed_to(&self, kind: EventKind) -> Vec<Webhook> {
+        self.hooks
+            .lock()
+            .expect("webhooks mutex poisoned")
+            .iter()
+            .filter(|hook| hook.events.contains(&kind))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a delivery to the log, dropping the oldest once it is full.
+    pub fn record(&self, delivery: Delivery) {
+        let mut deliveries = self.deliveries.lock().expect("deliveries mutex poisoned");
+        if deliveries.len() == MAX_DELIVERY_LOG {
+            deliveries.pop_front();
+        }
+        deliveries.push_back(delivery);
+    }
+
+    /// Applies `change` to a logged delivery, if it is still in the log.
+    pub fn update(&self, delivery_id: &str, change: impl FnOnce(&mut Delivery)) {
+        let mut deliveries = self.deliveries.lock().expect("deliveries mutex poisoned");
+        if let Some(delivery) = deliveries.iter_mut().find(|delivery| delivery.id == delivery_id) {
+            change(delivery);
+        }
+    }
+
+    /// Logged deliveries to one webhook, newest first.
+    pub fn deliveries(&self, webhook_id: &str) -> Vec<Delivery> {
+        self.deliveries
+            .lock()
+            .expect("deliveries mutex poisoned")
+            .iter()
+            .rev()
+            .filter(|delivery| delivery.webhook_id == webhook_id)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Whether an address is on the public internet. Loopback, private,
+/// link-local (which includes cloud metadata endpoints such as
+/// 169.254.169.254) and other special-purpose ranges are not.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        },
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(v4));
+            }
+            let segments = ip.segments();
+            // NAT64 addresses embed an IPv4 address in their last 32 bits.
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., high, low] = segments;
+                return is_public_address(IpAddr::V4(Ipv4Addr::from(((high as u32) << 16) | low as u32)));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00
+                || (segments[0] & 0xffc0) == 0xfe80
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+        }
+    }
+}
+
+/// Resolves webhook hosts like the system resolver, but drops non-public
+/// addresses, so a hostname cannot point deliveries at internal services.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.filter(|addr| is_public_address(addr.ip())).collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700::1111", "::ffff:8.8.8.8", "64:ff9b::808:808"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn internal_addresses_are_refused() {
+        let internal = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1",
+            "255.255.255.255", "224.0.0.1", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "::ffff:169.254.169.254",
+            "64:ff9b::a9fe:a9fe",
+        ];
+        for ip in internal {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use actix_web::{
+    HttpRequest, HttpResponse, ResponseError,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    error::JsonPayloadError,
+    http::{StatusCode, header::ContentType},
+    middleware::Next,
+};
+
+/// Every error the API reports. Rendered as an RFC 7807 `application/problem+json` body.
+#[derive(Debug)]
+pub enum ApiError {
+    EmptyInput,
+    InvalidRequest(String),
+    MissingField(String),
+    InvalidField { field: Option<String>, detail: String },
+    PayloadTooLarge(String),
+    UnparsableQuery(String),
+    ConflictingFilters(String),
+    StringNotFound,
+    NotInTrash,
+    CollectionNotFound,
+    WebhookNotFound,
+    NoMatches,
+    NotAcceptable,
+    StringAlreadyExists,
+    ImportConflict(usize),
+    BatchTooLarge(usize),
+    Internal(String),
+}
+
+impl ApiError {
+    /// Short, stable identifier of the problem type, used to build its `type` URI.
+    fn slug(&self) -> &'static str {
+        match self {
+            ApiError::EmptyInput => "empty-input",
+            ApiError::InvalidRequest(_) => "invalid-request",
+            ApiError::MissingField(_) => "missing-field",
+            ApiError::InvalidField { .. } => "invalid-field",
+            ApiError::PayloadTooLarge(_) => "payload-too-large",
+            ApiError::UnparsableQuery(_) => "unparsable-query",
+            ApiError::ConflictingFilters(_) => "conflicting-filters",
+            ApiError::StringNotFound => "string-not-found",
+            ApiError::NotInTrash => "not-in-trash",
+            ApiError::CollectionNotFound => "collection-not-found",
+            ApiError::WebhookNotFound => "webhook-not-found",
+            ApiError::NoMatches => "no-matches",
+            ApiError::NotAcceptable => "not-acceptable",
+            ApiError::StringAlreadyExists => "string-already-exists",
+            ApiError::ImportConflict(_) => "import-conflict",
+            ApiError::BatchTooLarge(_) => "batch-too-large",
+            ApiError::Internal(_) => "internal-error",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ApiError::EmptyInput => "Empty input",
+            ApiError::InvalidRequest(_) => "Invalid request",
+            ApiError::MissingField(_) => "Missing field",
+            ApiError::InvalidField { .. } => "Invalid field",
+            ApiError::PayloadTooLarge(_) => "Payload too large",
+            ApiError::UnparsableQuery(_) => "Unparsable query",
+            ApiError::ConflictingFilters(_) => "Conflicting filters",
+            ApiError::StringNotFound => "String not found",
+            ApiError::NotInTrash => "String not in trash",
+            ApiError::CollectionNotFound => "Collection not found",
+            ApiError::WebhookNotFound => "Webhook not found",
+            ApiError::NoMatches => "No matches",
+            ApiError::NotAcceptable => "Not acceptable",
+            ApiError::StringAlreadyExists => "String already exists",
+            ApiError::ImportConflict(_) => "Import conflict",
+            ApiError::BatchTooLarge(_) => "Batch too large",
+            ApiError::Internal(_) => "Internal server error",
+        }
+    }
+
+    /// Human-readable explanation specific to this occurrence.
+    pub fn detail(&self) -> String {
+        match self {
+            ApiError::EmptyInput => "Input string is empty. Please provide a valid string.".to_string(),
+            ApiError::InvalidRequest(detail) => detail.clone(),
+            ApiError::MissingField(field) => format!("Missing required field \"{}\"", field),
+            ApiError::InvalidField { field: Some(field), detail } => format!("Invalid value for field \"{}\": {}", field, detail),
+            ApiError::InvalidField { field: None, detail } => detail.clone(),
+            ApiError::PayloadTooLarge(detail) => detail.clone(),
+            ApiError::UnparsableQuery(detail) => format!("Could not parse the natural language query: {}", detail),
+            ApiError::ConflictingFilters(detail) => format!("The filters can never match: {}", detail),
+            ApiError::StringNotFound => "String does not exist in the system".to_string(),
+            ApiError::NotInTrash => "String does not exist in the trash".to_string(),
+            ApiError::CollectionNotFound => "Collection does not exist".to_string(),
+            ApiError::WebhookNotFound => "Webhook does not exist".to_string(),
+            ApiError::NoMatches => "No strings match the provided filters".to_string(),
+            ApiError::NotAcceptable => "None of the media types in the Accept header can be produced; supported are application/json, text/csv, application/x-ndjson and application/msgpack".to_string(),
+            ApiError::StringAlreadyExists => "String already exists in the system".to_string(),
+            ApiError::ImportConflict(count) => format!("{} imported strings already exist in the system; nothing was imported", count),
+            ApiError::BatchTooLarge(max) => format!("Batch exceeds the maximum of {} strings", max),
+            ApiError::Internal(detail) => detail.clone(),
+        }
+    }
+
+    /// Builds the problem document; `instance` is the request path when known.
+    pub fn problem(&self, instance: Option<&str>) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType(
+                "application/problem+json".parse().expect("valid mime type"),
+            ))
+            .body(self.problem_json(instance).to_string())
+    }
+
+    /// The problem document's fields, for reporting errors outside an HTTP response.
+    pub fn problem_json(&self, instance: Option<&str>) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "type": format!("/problems/{}", self.slug()),
+            "title": self.title(),
+            "status": self.status_code().as_u16(),
+            "detail": self.detail(),
+            "instance": instance,
+        });
+
+        match self {
+            ApiError::MissingField(field) | ApiError::InvalidField { field: Some(field), .. } => {
+                body["field"] = serde_json::json!(field);
+            },
+            _ => {}
+        }
+
+        body
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.detail())
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::EmptyInput | ApiError::InvalidRequest(_) | ApiError::UnparsableQuery(_) => StatusCode::BAD_REQUEST,
+            ApiError::MissingField(_) | ApiError::InvalidField { .. } | ApiError::ConflictingFilters(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::StringNotFound | ApiError::NotInTrash | ApiError::CollectionNotFound | ApiError::WebhookNotFound | ApiError::NoMatches => StatusCode::NOT_FOUND,
+            ApiError::NotAcceptable => StatusCode::NOT_ACCEPTABLE,
+            ApiError::StringAlreadyExists | ApiError::ImportConflict(_) => StatusCode::CONFLICT,
+            ApiError::BatchTooLarge(_) | ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.problem(None)
+    }
+}
+
+/// `ResponseError` has no access to the request, so this middleware re-renders
+/// `ApiError` responses with the request path as the problem `instance`.
+pub async fn problem_instance(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let path = req.path().to_string();
+    let res = next.call(req).await?;
+
+    let problem = res
+        .response()
+        .error()
+        .and_then(|e| e.as_error::<ApiError>())
+        .map(|e| e.problem(Some(&path)));
+
+    match problem {
+        Some(problem) => Ok(res.into_response(problem).map_into_boxed_body()),
+        None => Ok(res.map_into_boxed_body()),
+    }
+}
+
+/// Maps `web::Json` extractor failures: malformed JSON is a 400, while
+/// well-formed JSON of the wrong shape (missing field, wrong type) is a 422.
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let api_error = match err {
+        JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => ApiError::PayloadTooLarge(err.to_string()),
+        JsonPayloadError::ContentType => ApiError::InvalidRequest("Content-Type must be application/json".to_string()),
+        JsonPayloadError::Deserialize(e) if e.is_data() => serde_error_to_api_error(&e.to_string(), None),
+        JsonPayloadError::Deserialize(e) => ApiError::InvalidRequest(format!("Malformed JSON body: {}", e)),
+        other => ApiError::InvalidRequest(other.to_string()),
+    };
+
+    api_error.into()
+}
+
+pub(crate) fn serde_error_to_api_error(message: &str, field: Option<String>) -> ApiError {
+    if let Some(missing) = backticked_after(message, "missing field ") {
+        return ApiError::MissingField(missing);
+    }
+
+    let field = field
+        .or_else(|| backticked_after(message, "unknown field "))
+        .or_else(|| backticked_after(message, "duplicate field "));
+
+    ApiError::InvalidField {
+        field,
+        detail: message.to_string(),
+    }
+}
+
+/// Pulls the backticked name following `prefix` out of a serde error message,
+/// e.g. "missing field `value` at line 1 column 2" gives `value`.
+fn backticked_after(message: &str, prefix: &str) -> Option<String> {
+    let rest = &message[message.find(prefix)? + prefix.len()..];
+    let rest = rest.strip_prefix('`')?;
+    Some(rest[..rest.find('`')?].to_string())
+}
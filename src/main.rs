@@ -1,7 +1,12 @@
+#![allow(clippy::needless_return)]
+
 mod config;
+mod error;
+mod query;
 mod routes;
+mod tasks;
 use actix_cors::Cors;
-use actix_web::{App, HttpServer, http::header, middleware::Logger, web};
+use actix_web::{App, HttpServer, http::header, middleware::{Logger, from_fn}, web};
 use config::{config::Config, config_scope};
 use dotenv::dotenv;
 
@@ -28,9 +33,19 @@ async fn main() -> std::io::Result<()> {
 
     let app_state = web::Data::new(AppState { env: config });
 
+    actix_web::rt::spawn(tasks::trash::purge_expired_trash(app_state.clone()));
+    actix_web::rt::spawn(tasks::webhooks::dispatch_webhooks(app_state.clone()));
+    if app_state.env.nl_rules_path.is_some() {
+        actix_web::rt::spawn(tasks::nl_rules::reload_nl_rules(app_state.clone()));
+    }
+    if app_state.env.snapshot_path.is_some() {
+        actix_web::rt::spawn(tasks::snapshot::write_snapshots(app_state.clone()));
+    }
+
     println!("Server Started and running on {}:{}......", url, port);
     
-    HttpServer::new(move || {
+    let shutdown_state = app_state.clone();
+    let result = HttpServer::new(move || {
         let cors = Cors::default()
             .send_wildcard()
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
@@ -43,11 +58,18 @@ async fn main() -> std::io::Result<()> {
 
         App::new()
             .app_data(app_state.clone())
+            .app_data(web::JsonConfig::default().error_handler(error::json_error_handler))
             .configure(config_scope::config)
+            .wrap(from_fn(error::problem_instance))
             .wrap(cors)
             .wrap(Logger::default())
     })
     .bind((url, port))?
     .run()
-    .await
+    .await;
+
+    // `run` returns once SIGTERM/SIGINT has drained in-flight requests.
+    println!("Server stopped, saving state......");
+    config::snapshot::save_snapshot(&shutdown_state.env);
+    result
 }
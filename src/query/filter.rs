@@ -0,0 +1,102 @@
+/// A single constraint on stored strings. Produced both from structured query
+/// parameters and from natural-language queries; filters are ANDed together.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+    IsPalindrome(bool),
+    MinLength(usize),
+    MaxLength(usize),
+    WordCount(u32),
+    ContainsCharacter(char),
+    ExcludesCharacter(char),
+    Tag(String),
+    Metadata(String, String),
+}
+
+impl SearchFilter {
+    /// Builds a filter from its query-parameter name and a JSON value, as
+    /// used by rule files: `("min_length", 5)`, `("contains_character", "z")`.
+    pub fn from_pair(key: &str, value: &serde_json::Value) -> Result<SearchFilter, String> {
+        let as_usize = || value.as_u64().and_then(|v| usize::try_from(v).ok()).ok_or_else(|| format!("\"{}\" must be a non-negative integer", key));
+        let as_char = || {
+            let s = value.as_str().unwrap_or_default();
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("\"{}\" must be a single character", key)),
+            }
+        };
+
+        match key {
+            "is_palindrome" => value.as_bool().map(SearchFilter::IsPalindrome).ok_or_else(|| format!("\"{}\" must be a boolean", key)),
+            "min_length" => Ok(SearchFilter::MinLength(as_usize()?)),
+            "max_length" => Ok(SearchFilter::MaxLength(as_usize()?)),
+            "word_count" => {
+                let count = u32::try_from(as_usize()?).map_err(|_| format!("\"{}\" is too large", key))?;
+                Ok(SearchFilter::WordCount(count))
+            },
+            "contains_character" => Ok(SearchFilter::ContainsCharacter(as_char()?)),
+            "excludes_character" => Ok(SearchFilter::ExcludesCharacter(as_char()?)),
+            "tag" => value.as_str().map(|tag| SearchFilter::Tag(tag.to_string())).ok_or_else(|| format!("\"{}\" must be a string", key)),
+            _ => match key.strip_prefix("metadata.") {
+                Some(meta_key) => {
+                    let expected = match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    Ok(SearchFilter::Metadata(meta_key.to_string(), expected))
+                },
+                None => Err(format!("unknown filter \"{}\"", key)),
+            },
+        }
+    }
+}
+
+/// Property a natural-language query can order results by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Length,
+    WordCount,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub order: SortOrder,
+}
+
+impl Sort {
+    /// Orders results in place. The sort is stable, so ties keep insertion order.
+    pub fn apply(&self, results: &mut [crate::config::config::AnalysisResult]) {
+        results.sort_by(|a, b| {
+            let ordering = match self.key {
+                SortKey::Length => a.length.cmp(&b.length),
+                SortKey::WordCount => a.word_count.cmp(&b.word_count),
+                SortKey::CreatedAt => a.created_at.cmp(&b.created_at),
+            };
+            match self.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+    }
+
+    pub fn to_json(self) -> serde_json::Value {
+        let by = match self.key {
+            SortKey::Length => "length",
+            SortKey::WordCount => "word_count",
+            SortKey::CreatedAt => "created_at",
+        };
+        let order = match self.order {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        };
+        serde_json::json!({ "by": by, "order": order })
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Cmp, Lexicon};
+use crate::query::filter::{SortKey, SortOrder};
+
+pub static ENGLISH: Lexicon = Lexicon {
+    code: "en",
+    fillers: &[
+        &["all"], &["any"], &["every"], &["show"], &["me"], &["find"], &["list"], &["give"], &["get"],
+        &["string"], &["strings"], &["word"], &["words"], &["text"], &["texts"], &["value"], &["values"],
+        &["entry"], &["entries"], &["one"], &["ones"], &["that"], &["which"], &["who"], &["are"], &["is"],
+        &["be"], &["have"], &["has"], &["having"], &["with"], &["the"], &["a"], &["an"], &["of"], &["in"],
+        &["and"], &["also"], &["but"], &["long"], &["length"], &["in", "length"], &["total"], &["only"],
+    ],
+    negations: &[
+        &["do", "not"], &["does", "not"], &["is", "not"], &["are", "not"], &["don't"], &["doesn't"],
+        &["isn't"], &["aren't"], &["not"], &["non"], &["never"], &["no"],
+    ],
+    conjunctions: &[&["and"]],
+    disjunctions: &[&["or"], &["nor"]],
+    palindrome_words: &[&["palindrome"], &["palindromes"], &["palindromic"]],
+    comparators: &[
+        (&["no", "longer", "than"], Cmp::Le),
+        (&["no", "more", "than"], Cmp::Le),
+        (&["not", "more", "than"], Cmp::Le),
+        (&["no", "shorter", "than"], Cmp::Ge),
+        (&["no", "less", "than"], Cmp::Ge),
+        (&["no", "fewer", "than"], Cmp::Ge),
+        (&["longer", "than"], Cmp::Gt),
+        (&["more", "than"], Cmp::Gt),
+        (&["greater", "than"], Cmp::Gt),
+        (&["over"], Cmp::Gt),
+        (&["above"], Cmp::Gt),
+        (&["at", "least"], Cmp::Ge),
+        (&["minimum", "of"], Cmp::Ge),
+        (&["shorter", "than"], Cmp::Lt),
+        (&["less", "than"], Cmp::Lt),
+        (&["fewer", "than"], Cmp::Lt),
+        (&["under"], Cmp::Lt),
+        (&["below"], Cmp::Lt),
+        (&["at", "most"], Cmp::Le),
+        (&["maximum", "of"], Cmp::Le),
+        (&["up", "to"], Cmp::Le),
+        (&["exactly"], Cmp::Eq),
+        (&["precisely"], Cmp::Eq),
+    ],
+    between: &[&["between"], &["from"]],
+    range_separators: &[&["and"], &["to"]],
+    length_units: &[&["characters"], &["character"], &["chars"], &["char"], &["letters"], &["letter"]],
+    word_units: &[&["words"], &["word"]],
+    containment: &[
+        &["containing"], &["contains"], &["contain"], &["including"], &["includes"], &["include"],
+        &["having"], &["has"], &["have"], &["with"],
+    ],
+    negated_containment: &[&["without"], &["excluding"], &["lacking"]],
+    letter_nouns: &[&["letter"], &["character"], &["char"], &["vowel"], &["consonant"]],
+    named_letters: &[
+        (&["first", "vowel"], 'a'),
+        (&["last", "vowel"], 'u'),
+        (&["first", "consonant"], 'b'),
+        (&["last", "consonant"], 'z'),
+    ],
+    articles: &[&["the"], &["a"], &["an"]],
+    superlatives: &[
+        (&["longest"], SortKey::Length, SortOrder::Desc),
+        (&["shortest"], SortKey::Length, SortOrder::Asc),
+        (&["most", "words"], SortKey::WordCount, SortOrder::Desc),
+        (&["fewest", "words"], SortKey::WordCount, SortOrder::Asc),
+        (&["most", "recently", "added"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["most", "recent"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["newest"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["latest"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["oldest"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["earliest"], SortKey::CreatedAt, SortOrder::Asc),
+    ],
+    limit_words: &[&["top"], &["first"]],
+    numbers: &[
+        (&["zero"], 0), (&["one"], 1), (&["single"], 1), (&["two"], 2), (&["double"], 2), (&["three"], 3), (&["four"], 4),
+        (&["five"], 5), (&["six"], 6), (&["seven"], 7), (&["eight"], 8), (&["nine"], 9), (&["ten"], 10), (&["eleven"], 11),
+        (&["twelve"], 12), (&["thirteen"], 13), (&["fourteen"], 14), (&["fifteen"], 15), (&["sixteen"], 16),
+        (&["seventeen"], 17), (&["eighteen"], 18), (&["nineteen"], 19), (&["twenty"], 20), (&["thirty"], 30),
+        (&["forty"], 40), (&["fifty"], 50), (&["sixty"], 60), (&["seventy"], 70), (&["eighty"], 80), (&["ninety"], 90),
+    ],
+    multipliers: &[(&["hundred"], 100), (&["thousand"], 1000)],
+    number_joiners: &[],
+    teen_tens: &[],
+    templates: &[
+        "all single word palindromic strings",
+        "palindromic strings that contain the first vowel",
+        "strings longer than 10 characters",
+        "strings shorter than 5 characters",
+        "strings between 5 and 10 characters",
+        "strings with exactly two words",
+        "strings containing the letter z",
+        "strings that don't contain the letter e",
+        "non palindromic strings with at least 8 characters",
+        "the 5 longest palindromes",
+        "shortest strings containing z",
+        "top 10 newest strings",
+    ],
+};
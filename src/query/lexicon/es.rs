@@ -0,0 +1,117 @@
+use super::{Cmp, Lexicon};
+use crate::query::filter::{SortKey, SortOrder};
+
+pub static SPANISH: Lexicon = Lexicon {
+    code: "es",
+    fillers: &[
+        &["todas"], &["todos"], &["las"], &["los"], &["la"], &["el"], &["de"], &["del"], &["un"], &["una"],
+        &["cadena"], &["cadenas"], &["palabra"], &["palabras"], &["texto"], &["textos"], &["valor"], &["valores"],
+        &["que"], &["son"], &["es"], &["y"], &["también"], &["pero"], &["en"], &["longitud"], &["de", "longitud"],
+        &["en", "total"], &["solo"], &["sólo"], &["solamente"], &["muestra"], &["muéstrame"], &["encuentra"],
+        &["lista"], &["con"], &["tengan"], &["tienen"], &["tiene"],
+    ],
+    negations: &[&["no", "son"], &["no", "es"], &["no"], &["nunca"]],
+    conjunctions: &[&["y"], &["e"]],
+    disjunctions: &[&["o"], &["u"], &["ni"]],
+    palindrome_words: &[
+        &["palíndromo"], &["palíndromos"], &["palindromo"], &["palindromos"], &["palíndroma"], &["palíndromas"],
+        &["palindroma"], &["palindromas"], &["palindrómica"], &["palindrómicas"], &["palindromica"], &["palindromicas"],
+    ],
+    comparators: &[
+        (&["no", "más", "de"], Cmp::Le),
+        (&["no", "mas", "de"], Cmp::Le),
+        (&["no", "menos", "de"], Cmp::Ge),
+        (&["más", "largas", "que"], Cmp::Gt),
+        (&["más", "largos", "que"], Cmp::Gt),
+        (&["más", "larga", "que"], Cmp::Gt),
+        (&["más", "largo", "que"], Cmp::Gt),
+        (&["mas", "largas", "que"], Cmp::Gt),
+        (&["mas", "largos", "que"], Cmp::Gt),
+        (&["más", "cortas", "que"], Cmp::Lt),
+        (&["más", "cortos", "que"], Cmp::Lt),
+        (&["más", "corta", "que"], Cmp::Lt),
+        (&["más", "corto", "que"], Cmp::Lt),
+        (&["mas", "cortas", "que"], Cmp::Lt),
+        (&["mas", "cortos", "que"], Cmp::Lt),
+        (&["más", "de"], Cmp::Gt),
+        (&["mas", "de"], Cmp::Gt),
+        (&["menos", "de"], Cmp::Lt),
+        (&["al", "menos"], Cmp::Ge),
+        (&["como", "mínimo"], Cmp::Ge),
+        (&["como", "minimo"], Cmp::Ge),
+        (&["como", "máximo"], Cmp::Le),
+        (&["como", "maximo"], Cmp::Le),
+        (&["a", "lo", "sumo"], Cmp::Le),
+        (&["hasta"], Cmp::Le),
+        (&["exactamente"], Cmp::Eq),
+    ],
+    between: &[&["entre"]],
+    range_separators: &[&["y"]],
+    length_units: &[&["caracteres"], &["carácter"], &["caracter"], &["letras"], &["letra"]],
+    word_units: &[&["palabras"], &["palabra"]],
+    containment: &[
+        &["contienen"], &["contiene"], &["contengan"], &["conteniendo"], &["incluyen"], &["incluye"], &["incluyendo"],
+        &["con"], &["tienen"], &["tiene"], &["tengan"],
+    ],
+    negated_containment: &[&["sin"], &["excluyendo"]],
+    letter_nouns: &[&["letra"], &["carácter"], &["caracter"], &["vocal"], &["consonante"]],
+    named_letters: &[
+        (&["primera", "vocal"], 'a'),
+        (&["última", "vocal"], 'u'),
+        (&["ultima", "vocal"], 'u'),
+        (&["primera", "consonante"], 'b'),
+        (&["última", "consonante"], 'z'),
+        (&["ultima", "consonante"], 'z'),
+    ],
+    articles: &[&["la"], &["el"], &["una"], &["un"]],
+    superlatives: &[
+        (&["más", "largas"], SortKey::Length, SortOrder::Desc),
+        (&["más", "largos"], SortKey::Length, SortOrder::Desc),
+        (&["más", "larga"], SortKey::Length, SortOrder::Desc),
+        (&["más", "largo"], SortKey::Length, SortOrder::Desc),
+        (&["mas", "largas"], SortKey::Length, SortOrder::Desc),
+        (&["mas", "largos"], SortKey::Length, SortOrder::Desc),
+        (&["más", "cortas"], SortKey::Length, SortOrder::Asc),
+        (&["más", "cortos"], SortKey::Length, SortOrder::Asc),
+        (&["más", "corta"], SortKey::Length, SortOrder::Asc),
+        (&["más", "corto"], SortKey::Length, SortOrder::Asc),
+        (&["mas", "cortas"], SortKey::Length, SortOrder::Asc),
+        (&["mas", "cortos"], SortKey::Length, SortOrder::Asc),
+        (&["más", "palabras"], SortKey::WordCount, SortOrder::Desc),
+        (&["menos", "palabras"], SortKey::WordCount, SortOrder::Asc),
+        (&["más", "recientes"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["más", "reciente"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["más", "antiguas"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["más", "antiguos"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["más", "antigua"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["más", "antiguo"], SortKey::CreatedAt, SortOrder::Asc),
+    ],
+    limit_words: &[&["primeras"], &["primeros"], &["top"]],
+    numbers: &[
+        (&["una", "sola"], 1), (&["un", "solo"], 1), (&["una", "única"], 1), (&["un", "único"], 1),
+        (&["cero"], 0), (&["uno"], 1), (&["una"], 1), (&["un"], 1), (&["dos"], 2), (&["tres"], 3), (&["cuatro"], 4),
+        (&["cinco"], 5), (&["seis"], 6), (&["siete"], 7), (&["ocho"], 8), (&["nueve"], 9), (&["diez"], 10),
+        (&["once"], 11), (&["doce"], 12), (&["trece"], 13), (&["catorce"], 14), (&["quince"], 15),
+        (&["dieciséis"], 16), (&["dieciseis"], 16), (&["diecisiete"], 17), (&["dieciocho"], 18),
+        (&["diecinueve"], 19), (&["veinte"], 20), (&["veintiuno"], 21), (&["veintiuna"], 21), (&["veintiún"], 21),
+        (&["veintidós"], 22), (&["veintidos"], 22), (&["veintitrés"], 23), (&["veintitres"], 23), (&["veinticuatro"], 24),
+        (&["veinticinco"], 25), (&["veintiséis"], 26), (&["veintiseis"], 26), (&["veintisiete"], 27),
+        (&["veintiocho"], 28), (&["veintinueve"], 29), (&["treinta"], 30), (&["cuarenta"], 40), (&["cincuenta"], 50),
+        (&["sesenta"], 60), (&["setenta"], 70), (&["ochenta"], 80), (&["noventa"], 90),
+    ],
+    multipliers: &[(&["cien"], 100), (&["ciento"], 100), (&["mil"], 1000)],
+    number_joiners: &[&["y"]],
+    teen_tens: &[],
+    templates: &[
+        "cadenas palíndromas de una sola palabra",
+        "cadenas palíndromas con la primera vocal",
+        "cadenas de más de 10 caracteres",
+        "cadenas de menos de 5 caracteres",
+        "cadenas entre 5 y 10 caracteres",
+        "cadenas de dos palabras",
+        "cadenas que contienen la letra z",
+        "cadenas sin la letra e",
+        "los 5 palíndromos más largos",
+        "las cadenas más cortas con la letra z",
+    ],
+};
@@ -0,0 +1,109 @@
+use super::{Cmp, Lexicon};
+use crate::query::filter::{SortKey, SortOrder};
+
+pub static FRENCH: Lexicon = Lexicon {
+    code: "fr",
+    fillers: &[
+        &["toutes"], &["tous"], &["tout"], &["les"], &["la"], &["le"], &["l'"], &["des"], &["de"], &["d'"], &["du"],
+        &["un"], &["une"], &["chaîne"], &["chaînes"], &["chaine"], &["chaines"], &["mot"], &["mots"], &["texte"],
+        &["textes"], &["valeur"], &["valeurs"], &["qui"], &["que"], &["qu'"], &["dont"], &["sont"], &["est"],
+        &["ne"], &["n'"], &["et"], &["aussi"], &["mais"], &["en"], &["longueur"], &["de", "longueur"], &["au", "total"],
+        &["seulement"], &["uniquement"], &["montre"], &["moi"], &["trouve"], &["liste"], &["avec"], &["ayant"], &["ont"],
+    ],
+    negations: &[&["ne", "sont", "pas"], &["n'est", "pas"], &["pas"], &["non"], &["jamais"]],
+    conjunctions: &[&["et"]],
+    disjunctions: &[&["ou"], &["ni"]],
+    palindrome_words: &[
+        &["palindrome"], &["palindromes"], &["palindromique"], &["palindromiques"],
+    ],
+    comparators: &[
+        (&["pas", "plus", "de"], Cmp::Le),
+        (&["pas", "moins", "de"], Cmp::Ge),
+        (&["plus", "longue", "que"], Cmp::Gt),
+        (&["plus", "longues", "que"], Cmp::Gt),
+        (&["plus", "long", "que"], Cmp::Gt),
+        (&["plus", "longs", "que"], Cmp::Gt),
+        (&["plus", "courte", "que"], Cmp::Lt),
+        (&["plus", "courtes", "que"], Cmp::Lt),
+        (&["plus", "court", "que"], Cmp::Lt),
+        (&["plus", "courts", "que"], Cmp::Lt),
+        (&["plus", "de"], Cmp::Gt),
+        (&["moins", "de"], Cmp::Lt),
+        (&["au", "moins"], Cmp::Ge),
+        (&["au", "minimum"], Cmp::Ge),
+        (&["au", "plus"], Cmp::Le),
+        (&["au", "maximum"], Cmp::Le),
+        (&["jusqu'à"], Cmp::Le),
+        (&["exactement"], Cmp::Eq),
+    ],
+    between: &[&["entre"]],
+    range_separators: &[&["et"]],
+    length_units: &[&["caractères"], &["caractère"], &["caracteres"], &["caractere"], &["lettres"], &["lettre"]],
+    word_units: &[&["mots"], &["mot"]],
+    containment: &[
+        &["contenant"], &["contiennent"], &["contient"], &["contenir"], &["incluant"], &["incluent"], &["inclut"],
+        &["avec"], &["ayant"], &["ont"], &["a"],
+    ],
+    negated_containment: &[
+        &["sans"], &["excluant"], &["ne", "contiennent", "pas"], &["ne", "contient", "pas"], &["ne", "contenant", "pas"],
+    ],
+    letter_nouns: &[
+        &["lettre"], &["caractère"], &["caractere"], &["voyelle"], &["consonne"],
+    ],
+    named_letters: &[
+        (&["première", "voyelle"], 'a'),
+        (&["premiere", "voyelle"], 'a'),
+        (&["dernière", "voyelle"], 'u'),
+        (&["derniere", "voyelle"], 'u'),
+        (&["première", "consonne"], 'b'),
+        (&["premiere", "consonne"], 'b'),
+        (&["dernière", "consonne"], 'z'),
+        (&["derniere", "consonne"], 'z'),
+    ],
+    articles: &[&["la"], &["le"], &["l'"], &["une"], &["un"]],
+    superlatives: &[
+        (&["plus", "longues"], SortKey::Length, SortOrder::Desc),
+        (&["plus", "longue"], SortKey::Length, SortOrder::Desc),
+        (&["plus", "longs"], SortKey::Length, SortOrder::Desc),
+        (&["plus", "long"], SortKey::Length, SortOrder::Desc),
+        (&["plus", "courtes"], SortKey::Length, SortOrder::Asc),
+        (&["plus", "courte"], SortKey::Length, SortOrder::Asc),
+        (&["plus", "courts"], SortKey::Length, SortOrder::Asc),
+        (&["plus", "court"], SortKey::Length, SortOrder::Asc),
+        (&["plus", "de", "mots"], SortKey::WordCount, SortOrder::Desc),
+        (&["moins", "de", "mots"], SortKey::WordCount, SortOrder::Asc),
+        (&["plus", "récentes"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["plus", "récente"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["plus", "récents"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["plus", "récent"], SortKey::CreatedAt, SortOrder::Desc),
+        (&["plus", "anciennes"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["plus", "ancienne"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["plus", "anciens"], SortKey::CreatedAt, SortOrder::Asc),
+        (&["plus", "ancien"], SortKey::CreatedAt, SortOrder::Asc),
+    ],
+    limit_words: &[&["premières"], &["premiers"], &["top"]],
+    numbers: &[
+        (&["un", "seul"], 1), (&["une", "seule"], 1), (&["dix", "sept"], 17), (&["dix", "huit"], 18), (&["dix", "neuf"], 19),
+        (&["quatre", "vingts"], 80), (&["quatre", "vingt"], 80),
+        (&["zéro"], 0), (&["zero"], 0), (&["un"], 1), (&["une"], 1), (&["seul"], 1), (&["seule"], 1), (&["deux"], 2),
+        (&["trois"], 3), (&["quatre"], 4), (&["cinq"], 5), (&["six"], 6), (&["sept"], 7), (&["huit"], 8), (&["neuf"], 9),
+        (&["dix"], 10), (&["onze"], 11), (&["douze"], 12), (&["treize"], 13), (&["quatorze"], 14), (&["quinze"], 15),
+        (&["seize"], 16), (&["vingt"], 20), (&["trente"], 30), (&["quarante"], 40), (&["cinquante"], 50),
+        (&["soixante"], 60),
+    ],
+    multipliers: &[(&["cent"], 100), (&["cents"], 100), (&["mille"], 1000)],
+    number_joiners: &[&["et"]],
+    teen_tens: &[60, 80],
+    templates: &[
+        "chaînes palindromes d'un seul mot",
+        "chaînes palindromes contenant la première voyelle",
+        "chaînes de plus de 10 caractères",
+        "chaînes de moins de 5 caractères",
+        "chaînes entre 5 et 10 caractères",
+        "chaînes de deux mots",
+        "chaînes contenant la lettre z",
+        "chaînes qui ne contiennent pas la lettre e",
+        "les 5 palindromes les plus longs",
+        "les chaînes les plus courtes contenant z",
+    ],
+};
@@ -0,0 +1,134 @@
+mod en;
+mod es;
+mod fr;
+
+pub use en::ENGLISH;
+pub use es::SPANISH;
+pub use fr::FRENCH;
+
+use crate::query::filter::{SortKey, SortOrder};
+
+/// A phrase is a sequence of lowercased tokens that must appear consecutively.
+pub type Phrase = &'static [&'static str];
+
+/// Direction of a length or word-count comparison, as written in the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+impl Cmp {
+    /// The comparison meant by "not <self>", e.g. "not longer than" is "at most".
+    pub fn negate(self) -> Option<Cmp> {
+        match self {
+            Cmp::Gt => Some(Cmp::Le),
+            Cmp::Ge => Some(Cmp::Lt),
+            Cmp::Lt => Some(Cmp::Ge),
+            Cmp::Le => Some(Cmp::Gt),
+            Cmp::Eq => None,
+        }
+    }
+}
+
+/// A language pack: the vocabulary the grammar is written against. Keeping
+/// it separate from the parser means the grammar itself never mentions a word
+/// of any particular language.
+pub struct Lexicon {
+    /// ISO 639-1 code, as accepted by the `lang` query parameter.
+    pub code: &'static str,
+    /// Words that carry no meaning for filtering ("all", "strings", "the").
+    pub fillers: &'static [Phrase],
+    pub negations: &'static [Phrase],
+    /// Words joining items of a list ("containing x and y").
+    pub conjunctions: &'static [Phrase],
+    /// Words offering alternatives ("x or y"); only meaningful after an exclusion.
+    pub disjunctions: &'static [Phrase],
+    pub palindrome_words: &'static [Phrase],
+    /// Comparison phrases; longer phrases must come before their prefixes.
+    pub comparators: &'static [(Phrase, Cmp)],
+    pub between: &'static [Phrase],
+    pub range_separators: &'static [Phrase],
+    pub length_units: &'static [Phrase],
+    pub word_units: &'static [Phrase],
+    /// Verbs and prepositions introducing a character ("containing", "with").
+    pub containment: &'static [Phrase],
+    /// Containment words that also negate ("without", "excluding").
+    pub negated_containment: &'static [Phrase],
+    /// Nouns that may precede a single letter ("the letter z").
+    pub letter_nouns: &'static [Phrase],
+    /// Phrases that name a specific letter ("the first vowel").
+    pub named_letters: &'static [(Phrase, char)],
+    pub articles: &'static [Phrase],
+    /// Superlatives that order the results ("longest", "newest").
+    pub superlatives: &'static [(Phrase, SortKey, SortOrder)],
+    /// Words introducing how many results to return ("top 5").
+    pub limit_words: &'static [Phrase],
+    /// Number words; phrases allow "un seul" or "una sola".
+    pub numbers: &'static [(Phrase, usize)],
+    /// Multipliers applied to the number read so far ("two hundred").
+    pub multipliers: &'static [(Phrase, usize)],
+    /// Words joining a tens word to the units after it ("treinta y uno", "vingt et un").
+    pub number_joiners: &'static [Phrase],
+    /// Tens followed by ten to nineteen rather than a digit ("soixante-dix", "quatre-vingt-douze").
+    pub teen_tens: &'static [usize],
+    /// Example queries the grammar understands, offered as suggestions.
+    pub templates: &'static [&'static str],
+}
+
+/// Every supported language; the first is the fallback when detection is inconclusive.
+pub static LANGUAGE_PACKS: &[&Lexicon] = &[&ENGLISH, &FRENCH, &SPANISH];
+
+/// Looks up a language pack by its `lang` code.
+pub fn language_pack(code: &str) -> Option<&'static Lexicon> {
+    LANGUAGE_PACKS.iter().copied().find(|pack| pack.code.eq_ignore_ascii_case(code))
+}
+
+/// Picks the language pack that recognises the most words of the query.
+pub fn detect_language(query: &str) -> &'static Lexicon {
+    let tokens = crate::query::tokenizer::tokenize(query);
+    let mut best = LANGUAGE_PACKS[0];
+    let mut best_score = 0;
+
+    for pack in LANGUAGE_PACKS.iter().copied() {
+        let score = tokens.iter().filter(|token| pack.knows(&token.text)).count();
+        if score > best_score {
+            best = pack;
+            best_score = score;
+        }
+    }
+
+    best
+}
+
+impl Lexicon {
+    /// Whether the word appears anywhere in this pack's vocabulary.
+    fn knows(&self, word: &str) -> bool {
+        let in_phrases = |phrases: &[Phrase]| phrases.iter().any(|p| p.contains(&word));
+        let in_pairs = |pairs: &[(Phrase, usize)]| pairs.iter().any(|(p, _)| p.contains(&word));
+
+        in_phrases(self.fillers)
+            || in_phrases(self.negations)
+            || in_phrases(self.conjunctions)
+            || in_phrases(self.disjunctions)
+            || in_phrases(self.palindrome_words)
+            || self.comparators.iter().any(|(p, _)| p.contains(&word))
+            || in_phrases(self.between)
+            || in_phrases(self.range_separators)
+            || in_phrases(self.length_units)
+            || in_phrases(self.word_units)
+            || in_phrases(self.containment)
+            || in_phrases(self.negated_containment)
+            || in_phrases(self.letter_nouns)
+            || self.named_letters.iter().any(|(p, _)| p.contains(&word))
+            || in_phrases(self.articles)
+            || self.superlatives.iter().any(|(p, _, _)| p.contains(&word))
+            || in_phrases(self.limit_words)
+            || in_pairs(self.numbers)
+            || in_pairs(self.multipliers)
+            || in_phrases(self.number_joiners)
+    }
+}
@@ -0,0 +1,6 @@
+pub mod filter;
+pub mod lexicon;
+pub mod parser;
+pub mod rules;
+pub mod suggest;
+pub mod tokenizer;
@@ -0,0 +1,705 @@
+use crate::error::ApiError;
+use crate::query::filter::{SearchFilter, Sort};
+use crate::query::lexicon::{Cmp, Lexicon, Phrase};
+use crate::query::rules::{CaptureKind, Captures, PatternPart, PhraseRule};
+use crate::query::tokenizer::{Token, tokenize};
+
+/// A run of the query recognised as one clause, e.g. "not longer than ten characters".
+#[derive(Debug, Clone)]
+pub struct PhraseMatch {
+    /// Byte range of the clause in the original query.
+    pub start: usize,
+    pub end: usize,
+    /// Number of tokens the clause spans.
+    pub tokens: usize,
+    /// Name of the grammar rule, or of the rules-file rule, that matched.
+    pub rule: String,
+    pub filters: Vec<SearchFilter>,
+    pub sort: Option<Sort>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedQuery {
+    pub filters: Vec<SearchFilter>,
+    /// Order to return matches in, from a superlative such as "longest".
+    pub sort: Option<Sort>,
+    /// Maximum number of matches to return ("the 5 longest", "top 10").
+    pub limit: Option<usize>,
+    pub matches: Vec<PhraseMatch>,
+    /// Words that were neither part of a clause nor filler.
+    pub leftover: Vec<Token>,
+}
+
+impl ParsedQuery {
+    /// Share of the meaningful words (filler aside) that were understood,
+    /// from 0.0 (nothing recognised) to 1.0 (no leftover words).
+    pub fn confidence(&self) -> f64 {
+        let recognised: usize = self.matches.iter().map(|m| m.tokens).sum();
+        let total = recognised + self.leftover.len();
+        if recognised == 0 {
+            return 0.0;
+        }
+        recognised as f64 / total as f64
+    }
+}
+
+enum Unit {
+    Length,
+    Words,
+}
+
+/// A clause recognised at the current position, before its span is known.
+struct Clause {
+    rule: String,
+    filters: Vec<SearchFilter>,
+    sort: Option<Sort>,
+    limit: Option<usize>,
+}
+
+impl Clause {
+    fn filters(rule: impl Into<String>, filters: Vec<SearchFilter>) -> Clause {
+        Clause {
+            rule: rule.into(),
+            filters,
+            sort: None,
+            limit: None,
+        }
+    }
+}
+
+/// Parses a natural-language query into filters that are ANDed together,
+/// plus an optional sort order and limit.
+///
+/// The grammar is a sequence of clauses separated by filler words, each
+/// optionally preceded by a negation:
+///
+/// ```text
+/// clause     := palindrome | comparison | range | exact | containment | ordering
+/// comparison := COMPARATOR number unit?        "at least 5 characters"
+/// range      := BETWEEN number SEP number unit? "between ten and 20 letters"
+/// exact      := number unit                    "single word", "3 characters"
+/// containment:= CONTAIN? letter                "containing the letter z"
+/// ordering   := LIMIT? number? SUPERLATIVE     "the 5 longest", "top 3 newest"
+///             | LIMIT number                   "top 10"
+/// ```
+///
+/// Rules loaded from the rules file are tried before the built-in clauses,
+/// so they can add phrasings or override built-in ones.
+pub fn parse(query: &str, lexicon: &Lexicon, rules: &[PhraseRule]) -> Result<ParsedQuery, ApiError> {
+    let tokens = tokenize(query);
+    let mut parser = Parser {
+        tokens: &tokens,
+        lexicon,
+        rules,
+        pos: 0,
+    };
+
+    let mut matches = Vec::new();
+    let mut leftover = Vec::new();
+    let mut negation: Option<usize> = None;
+    let mut sort = None;
+    let mut limit = None;
+
+    while parser.pos < tokens.len() {
+        let clause_start = negation.unwrap_or(parser.pos);
+        if let Some(clause) = parser.clause(negation.is_some())? {
+            if clause.sort.is_some() {
+                if sort.is_some() {
+                    return Err(ApiError::UnparsableQuery("only one sort order can be given".to_string()));
+                }
+                sort = clause.sort;
+            }
+            if clause.limit.is_some() {
+                if limit.is_some() {
+                    return Err(ApiError::UnparsableQuery("only one result count can be given".to_string()));
+                }
+                limit = clause.limit;
+            }
+            matches.push(PhraseMatch {
+                start: tokens[clause_start].start,
+                end: tokens[parser.pos - 1].end,
+                tokens: parser.pos - clause_start,
+                rule: clause.rule,
+                filters: clause.filters,
+                sort: clause.sort,
+                limit: clause.limit,
+            });
+            negation = None;
+        } else if negation.is_none() && let Some(len) = parser.phrase_at(lexicon.negations) {
+            negation = Some(parser.pos);
+            parser.pos += len;
+        } else if let Some(len) = parser.phrase_at(lexicon.fillers) {
+            parser.pos += len;
+        } else {
+            leftover.push(tokens[parser.pos].clone());
+            parser.pos += 1;
+        }
+    }
+
+    // A negation with nothing after it to negate.
+    if let Some(index) = negation {
+        leftover.push(tokens[index].clone());
+    }
+
+    let filters = matches.iter().flat_map(|m| m.filters.iter().cloned()).collect();
+
+    Ok(ParsedQuery {
+        filters,
+        sort,
+        limit,
+        matches,
+        leftover,
+    })
+}
+
+#[derive(Clone, Copy)]
+struct Parser<'a> {
+    tokens: &'a [Token],
+    lexicon: &'a Lexicon,
+    rules: &'a [PhraseRule],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Tries each clause rule at the current position. On success the
+    /// position is advanced past the clause; otherwise it is left untouched.
+    fn clause(&mut self, negated: bool) -> Result<Option<Clause>, ApiError> {
+        if let Some((rule, filters)) = self.configured_rule(negated)? {
+            return Ok(Some(Clause::filters(rule, filters)));
+        }
+        if let Some((rule, filters)) = self.builtin_clause(negated)? {
+            return Ok(Some(Clause::filters(rule, filters)));
+        }
+        if let Some((sort, limit)) = self.ordering() {
+            if negated {
+                return Err(ApiError::UnparsableQuery("a sort order or result count cannot be negated".to_string()));
+            }
+            return Ok(Some(Clause {
+                rule: "ordering".to_string(),
+                filters: Vec::new(),
+                sort,
+                limit,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Reads a sort order and/or result count: "the 5 longest", "top 10",
+    /// or "les 5 chaînes les plus longues" with filler before the superlative.
+    fn ordering(&mut self) -> Option<(Option<Sort>, Option<usize>)> {
+        let start = self.pos;
+
+        let limit_word = self.phrase_at(self.lexicon.limit_words);
+        if let Some(len) = limit_word {
+            self.pos += len;
+        }
+        let limit = self.number();
+        if limit_word.is_some() && limit.is_none() {
+            self.pos = start;
+            return None;
+        }
+
+        let before_fillers = self.pos;
+        while self.superlative_at().is_none() && let Some(len) = self.phrase_at(self.lexicon.fillers) {
+            self.pos += len;
+        }
+
+        let sort = match self.superlative_at() {
+            Some((len, sort)) => {
+                self.pos += len;
+                Some(sort)
+            },
+            None if limit_word.is_some() => {
+                self.pos = before_fillers;
+                None
+            },
+            None => {
+                self.pos = start;
+                return None;
+            }
+        };
+
+        Some((sort, limit))
+    }
+
+    fn superlative_at(&self) -> Option<(usize, Sort)> {
+        self.lexicon
+            .superlatives
+            .iter()
+            .find(|(p, _, _)| self.matches_phrase(p))
+            .map(|(p, key, order)| (p.len(), Sort { key: *key, order: *order }))
+    }
+
+    /// Tries the rules-file rules at the current position, preferring the one
+    /// matching the most tokens.
+    fn configured_rule(&mut self, negated: bool) -> Result<Option<(String, Vec<SearchFilter>)>, ApiError> {
+        let start = self.pos;
+        let mut best: Option<(usize, &PhraseRule, Captures)> = None;
+
+        for rule in self.rules {
+            self.pos = start;
+            if let Some(captures) = self.match_pattern(&rule.pattern)
+                && best.as_ref().is_none_or(|(end, _, _)| self.pos > *end)
+            {
+                best = Some((self.pos, rule, captures));
+            }
+        }
+
+        let Some((end, rule, captures)) = best else {
+            self.pos = start;
+            return Ok(None);
+        };
+        self.pos = end;
+
+        let filters = rule
+            .filters(&captures)
+            .map_err(|e| ApiError::UnparsableQuery(format!("rule \"{}\": {}", rule.name, e)))?;
+        let filters = if negated { negate_filters(&rule.name, filters)? } else { filters };
+        Ok(Some((rule.name.clone(), filters)))
+    }
+
+    fn match_pattern(&mut self, pattern: &[PatternPart]) -> Option<Captures> {
+        let mut captures = Vec::new();
+        for part in pattern {
+            match part {
+                PatternPart::Word(word) => {
+                    if self.tokens.get(self.pos)?.text != *word {
+                        return None;
+                    }
+                    self.pos += 1;
+                },
+                PatternPart::Capture { name, kind } => {
+                    let value = match kind {
+                        CaptureKind::Number => serde_json::json!(self.number()?),
+                        CaptureKind::Letter => {
+                            let c = self.single_letter()?;
+                            self.pos += 1;
+                            serde_json::json!(c)
+                        },
+                        CaptureKind::Word => {
+                            let token = self.tokens.get(self.pos)?;
+                            self.pos += 1;
+                            serde_json::json!(token.text)
+                        },
+                    };
+                    captures.push((name.clone(), value));
+                },
+            }
+        }
+        Some(captures)
+    }
+
+    fn builtin_clause(&mut self, negated: bool) -> Result<Option<(&'static str, Vec<SearchFilter>)>, ApiError> {
+        let start = self.pos;
+
+        if let Some(len) = self.phrase_at(self.lexicon.palindrome_words) {
+            self.pos += len;
+            return Ok(Some(("palindrome", vec![SearchFilter::IsPalindrome(!negated)])));
+        }
+
+        if let Some(cmp) = self.comparator() {
+            if let Some(number) = self.number() {
+                let unit = self.unit().unwrap_or(Unit::Length);
+                let cmp = if negated { negate(cmp)? } else { cmp };
+                return Ok(Some(("comparison", comparison_filters(cmp, number, unit)?)));
+            }
+            self.pos = start;
+        }
+
+        if let Some(len) = self.phrase_at(self.lexicon.between) {
+            self.pos += len;
+            if let Some(low) = self.number()
+                && let Some(sep) = self.phrase_at(self.lexicon.range_separators)
+            {
+                self.pos += sep;
+                if let Some(high) = self.number() {
+                    if negated {
+                        return Err(ApiError::UnparsableQuery("a negated range cannot be expressed as filters".to_string()));
+                    }
+                    if let Some(Unit::Words) = self.unit() {
+                        return Err(ApiError::UnparsableQuery("word counts can only be matched exactly".to_string()));
+                    }
+                    let (low, high) = (low.min(high), low.max(high));
+                    return Ok(Some(("range", vec![SearchFilter::MinLength(low), SearchFilter::MaxLength(high)])));
+                }
+            }
+            self.pos = start;
+        }
+
+        if let Some(number) = self.number() {
+            if let Some(unit) = self.unit() {
+                if negated {
+                    return Err(ApiError::UnparsableQuery("an exact length or word count cannot be negated".to_string()));
+                }
+                let rule = match unit {
+                    Unit::Length => "exact_length",
+                    Unit::Words => "word_count",
+                };
+                return Ok(Some((rule, comparison_filters(Cmp::Eq, number, unit)?)));
+            }
+            self.pos = start;
+        }
+
+        let excludes = if let Some(len) = self.phrase_at(self.lexicon.negated_containment) {
+            self.pos += len;
+            !negated
+        } else if let Some(len) = self.phrase_at(self.lexicon.containment) {
+            self.pos += len;
+            negated
+        } else {
+            // A bare "letter z" is still a containment clause, but a bare "z" is not.
+            if self.phrase_at(self.lexicon.letter_nouns).is_none() && self.named_letter_at().is_none() {
+                return Ok(None);
+            }
+            negated
+        };
+
+        let Some(first) = self.letter() else {
+            self.pos = start;
+            return Ok(None);
+        };
+
+        // "containing x and y" requires both; "without x or y" excludes both.
+        let mut letters = vec![first];
+        loop {
+            let before = self.pos;
+            if let Some(len) = self.phrase_at(self.lexicon.conjunctions) {
+                self.pos += len;
+            } else if let Some(len) = self.phrase_at(self.lexicon.disjunctions) {
+                self.pos += len;
+                let mut probe = *self;
+                if !excludes && probe.listed_letter().is_some() {
+                    return Err(ApiError::UnparsableQuery("strings can only be required to contain all of the listed characters".to_string()));
+                }
+            } else {
+                break;
+            }
+            match self.listed_letter() {
+                Some(c) => letters.push(c),
+                None => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+
+        let filters = letters
+            .into_iter()
+            .map(|c| if excludes { SearchFilter::ExcludesCharacter(c) } else { SearchFilter::ContainsCharacter(c) })
+            .collect();
+        Ok(Some(("contains", filters)))
+    }
+
+    /// Length in tokens of the first phrase matching at the current position.
+    fn phrase_at(&self, phrases: &[Phrase]) -> Option<usize> {
+        phrases.iter().find(|p| self.matches_phrase(p)).map(|p| p.len())
+    }
+
+    fn matches_phrase(&self, phrase: Phrase) -> bool {
+        self.matches_phrase_at(self.pos, phrase)
+    }
+
+    fn matches_phrase_at(&self, pos: usize, phrase: Phrase) -> bool {
+        let rest = &self.tokens[pos.min(self.tokens.len())..];
+        rest.len() >= phrase.len() && phrase.iter().zip(rest).all(|(word, token)| token.text == *word)
+    }
+
+    fn comparator(&mut self) -> Option<Cmp> {
+        let (phrase, cmp) = self.lexicon.comparators.iter().find(|(p, _)| self.matches_phrase(p))?;
+        self.pos += phrase.len();
+        Some(*cmp)
+    }
+
+    fn unit(&mut self) -> Option<Unit> {
+        if let Some(len) = self.phrase_at(self.lexicon.length_units) {
+            self.pos += len;
+            return Some(Unit::Length);
+        }
+        if let Some(len) = self.phrase_at(self.lexicon.word_units) {
+            self.pos += len;
+            return Some(Unit::Words);
+        }
+        None
+    }
+
+    /// Reads a number written as digits ("12") or words ("twenty five",
+    /// "two hundred fifty", "treinta y uno", "quatre-vingt-dix").
+    fn number(&mut self) -> Option<usize> {
+        let token = self.tokens.get(self.pos)?;
+        if token.text.chars().all(|c| c.is_ascii_digit()) {
+            let number = token.text.parse().ok()?;
+            self.pos += 1;
+            return Some(number);
+        }
+
+        let mut total: usize = 0;
+        let mut current: Option<usize> = None;
+        // Largest value the next number word may have, so "twenty five" adds
+        // up but "five five" or "twenty thirty" are read as separate numbers.
+        let mut slot = usize::MAX;
+        // Whether the last word was a round tens word, which a joiner may follow.
+        let mut after_tens = false;
+        while self.pos < self.tokens.len() {
+            if let Some((phrase, value)) = self.lexicon.numbers.iter().find(|(p, _)| self.matches_phrase(p)) {
+                if *value >= slot {
+                    break;
+                }
+                current = Some(current.unwrap_or(0) + value);
+                after_tens = *value >= 20 && value % 10 == 0;
+                slot = if self.lexicon.teen_tens.contains(value) {
+                    20
+                } else if *value >= 20 {
+                    10
+                } else {
+                    1
+                };
+                self.pos += phrase.len();
+            } else if after_tens && let Some(len) = self.phrase_at(self.lexicon.number_joiners) {
+                // Only a joined units word belongs to the number; "entre vingt et
+                // trente" keeps "et" as the range separator.
+                let joined = self.pos + len;
+                let fits = self.lexicon.numbers.iter().any(|(p, value)| *value < slot && *value > 0 && self.matches_phrase_at(joined, p));
+                if !fits {
+                    break;
+                }
+                after_tens = false;
+                self.pos = joined;
+            } else if let Some((phrase, factor)) = self.lexicon.multipliers.iter().find(|(p, _)| self.matches_phrase(p)) {
+                // "cien", "cent" and "mille" stand alone for one hundred or thousand.
+                let n = current.unwrap_or(1);
+                let scaled = n.checked_mul(*factor)?;
+                if *factor >= 1000 {
+                    total = total.checked_add(scaled)?;
+                    current = Some(0);
+                } else {
+                    current = Some(scaled);
+                }
+                slot = *factor;
+                after_tens = false;
+                self.pos += phrase.len();
+            } else {
+                break;
+            }
+        }
+
+        current.map(|n| total + n)
+    }
+
+    /// Reads a letter reference: "z", "the letter z", "a vowel e", "the first vowel".
+    fn letter(&mut self) -> Option<char> {
+        let start = self.pos;
+
+        // Try with a leading article first; "a" may itself be the letter.
+        for skip_article in [true, false] {
+            self.pos = start;
+            if skip_article {
+                let Some(len) = self.phrase_at(self.lexicon.articles) else { continue };
+                self.pos += len;
+            }
+            if let Some((len, c)) = self.named_letter_at() {
+                self.pos += len;
+                return Some(c);
+            }
+            if let Some(len) = self.phrase_at(self.lexicon.letter_nouns) {
+                self.pos += len;
+            }
+            if let Some(c) = self.single_letter() {
+                self.pos += 1;
+                return Some(c);
+            }
+        }
+
+        self.pos = start;
+        None
+    }
+
+    /// Reads a further letter in a list. A lone "a" is only the letter when
+    /// the list ends there, so "z and a palindrome" is not read as a list.
+    fn listed_letter(&mut self) -> Option<char> {
+        if self.phrase_at(self.lexicon.articles).is_some() && self.single_letter().is_some() {
+            let next = Parser { pos: self.pos + 1, ..*self };
+            let continues = next.pos < self.tokens.len()
+                && next.phrase_at(self.lexicon.conjunctions).is_none()
+                && next.phrase_at(self.lexicon.disjunctions).is_none()
+                && next.phrase_at(self.lexicon.letter_nouns).is_none()
+                && next.named_letter_at().is_none();
+            if continues {
+                return None;
+            }
+        }
+        self.letter()
+    }
+
+    fn named_letter_at(&self) -> Option<(usize, char)> {
+        self.lexicon.named_letters.iter().find(|(p, _)| self.matches_phrase(p)).map(|(p, c)| (p.len(), *c))
+    }
+
+    fn single_letter(&self) -> Option<char> {
+        let token = self.tokens.get(self.pos)?;
+        let mut chars = token.text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_alphabetic() => Some(c),
+            _ => None,
+        }
+    }
+}
+
+/// Negates the single filter a rules-file rule produced; several filters
+/// negate into an "or", which filters cannot express.
+fn negate_filters(rule: &str, filters: Vec<SearchFilter>) -> Result<Vec<SearchFilter>, ApiError> {
+    let cannot_negate = || ApiError::UnparsableQuery(format!("the phrase matched by rule \"{}\" cannot be negated", rule));
+    let [filter] = <[SearchFilter; 1]>::try_from(filters).map_err(|_| cannot_negate())?;
+
+    let negated = match filter {
+        SearchFilter::IsPalindrome(value) => SearchFilter::IsPalindrome(!value),
+        SearchFilter::ContainsCharacter(c) => SearchFilter::ExcludesCharacter(c),
+        SearchFilter::ExcludesCharacter(c) => SearchFilter::ContainsCharacter(c),
+        SearchFilter::MinLength(min) => SearchFilter::MaxLength(min.checked_sub(1).ok_or_else(|| ApiError::ConflictingFilters("no string is shorter than 0 characters".to_string()))?),
+        SearchFilter::MaxLength(max) => SearchFilter::MinLength(max.checked_add(1).ok_or_else(cannot_negate)?),
+        _ => return Err(cannot_negate()),
+    };
+    Ok(vec![negated])
+}
+
+fn negate(cmp: Cmp) -> Result<Cmp, ApiError> {
+    cmp.negate().ok_or_else(|| ApiError::UnparsableQuery("an exact length or word count cannot be negated".to_string()))
+}
+
+fn comparison_filters(cmp: Cmp, number: usize, unit: Unit) -> Result<Vec<SearchFilter>, ApiError> {
+    if let Unit::Words = unit {
+        if cmp != Cmp::Eq {
+            return Err(ApiError::UnparsableQuery("word counts can only be matched exactly".to_string()));
+        }
+        let count = u32::try_from(number).map_err(|_| ApiError::ConflictingFilters(format!("no string has {} words", number)))?;
+        return Ok(vec![SearchFilter::WordCount(count)]);
+    }
+
+    let filters = match cmp {
+        Cmp::Gt => {
+            let min = number.checked_add(1).ok_or_else(|| ApiError::ConflictingFilters(format!("no string is longer than {} characters", number)))?;
+            vec![SearchFilter::MinLength(min)]
+        },
+        Cmp::Ge => vec![SearchFilter::MinLength(number)],
+        Cmp::Lt => {
+            let max = number.checked_sub(1).ok_or_else(|| ApiError::ConflictingFilters("no string is shorter than 0 characters".to_string()))?;
+            vec![SearchFilter::MaxLength(max)]
+        },
+        Cmp::Le => vec![SearchFilter::MaxLength(number)],
+        Cmp::Eq => vec![SearchFilter::MinLength(number), SearchFilter::MaxLength(number)],
+    };
+
+    Ok(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::lexicon::{ENGLISH, FRENCH, SPANISH};
+
+    fn read_number(query: &str, lexicon: &Lexicon) -> Option<usize> {
+        let tokens = tokenize(query);
+        let mut parser = Parser {
+            tokens: &tokens,
+            lexicon,
+            rules: &[],
+            pos: 0,
+        };
+        parser.number()
+    }
+
+    fn filters(query: &str, lexicon: &Lexicon) -> Vec<SearchFilter> {
+        parse(query, lexicon, &[]).expect("query should parse").filters
+    }
+
+    #[test]
+    fn english_numbers() {
+        let cases = [
+            ("12", 12),
+            ("seven", 7),
+            ("nineteen", 19),
+            ("twenty five", 25),
+            ("twenty-five", 25),
+            ("ninety nine", 99),
+            ("two hundred fifty", 250),
+            ("three thousand two hundred", 3200),
+        ];
+        for (words, expected) in cases {
+            assert_eq!(read_number(words, &ENGLISH), Some(expected), "{}", words);
+        }
+        assert_eq!(read_number("five five", &ENGLISH), Some(5));
+        assert_eq!(read_number("twenty thirty", &ENGLISH), Some(20));
+        assert_eq!(read_number("characters", &ENGLISH), None);
+    }
+
+    #[test]
+    fn french_numbers() {
+        let cases = [
+            ("dix-sept", 17),
+            ("vingt", 20),
+            ("vingt et un", 21),
+            ("vingt-deux", 22),
+            ("trente et un", 31),
+            ("soixante", 60),
+            ("soixante-neuf", 69),
+            ("soixante-dix", 70),
+            ("soixante et onze", 71),
+            ("soixante-douze", 72),
+            ("soixante-dix-neuf", 79),
+            ("quatre-vingts", 80),
+            ("quatre-vingt-un", 81),
+            ("quatre-vingt-dix", 90),
+            ("quatre-vingt-onze", 91),
+            ("quatre-vingt-dix-neuf", 99),
+            ("deux cent quatre-vingt-dix", 290),
+            ("quatre", 4),
+            ("cent", 100),
+            ("mille deux cents", 1200),
+        ];
+        for (words, expected) in cases {
+            assert_eq!(read_number(words, &FRENCH), Some(expected), "{}", words);
+        }
+        // "et" before a larger number is the range separator, not part of the number.
+        assert_eq!(read_number("vingt et trente", &FRENCH), Some(20));
+        assert_eq!(read_number("dix et onze", &FRENCH), Some(10));
+    }
+
+    #[test]
+    fn spanish_numbers() {
+        let cases = [
+            ("dieciséis", 16),
+            ("veinte", 20),
+            ("veintiuno", 21),
+            ("veintidós", 22),
+            ("veintinueve", 29),
+            ("treinta y uno", 31),
+            ("noventa y nueve", 99),
+            ("ciento veinticinco", 125),
+            ("ciento treinta y dos", 132),
+            ("dos mil", 2000),
+            ("cien", 100),
+            ("mil veinte", 1020),
+        ];
+        for (words, expected) in cases {
+            assert_eq!(read_number(words, &SPANISH), Some(expected), "{}", words);
+        }
+        assert_eq!(read_number("veinte y treinta", &SPANISH), Some(20));
+    }
+
+    #[test]
+    fn compound_numbers_in_queries() {
+        assert_eq!(filters("chaînes de plus de soixante-dix caractères", &FRENCH), vec![SearchFilter::MinLength(71)]);
+        assert_eq!(filters("chaînes de moins de vingt et un caractères", &FRENCH), vec![SearchFilter::MaxLength(20)]);
+        assert_eq!(
+            filters("chaînes entre vingt et trente caractères", &FRENCH),
+            vec![SearchFilter::MinLength(20), SearchFilter::MaxLength(30)]
+        );
+        assert_eq!(filters("cadenas de más de veintiuno caracteres", &SPANISH), vec![SearchFilter::MinLength(22)]);
+        assert_eq!(filters("cadenas de menos de treinta y uno caracteres", &SPANISH), vec![SearchFilter::MaxLength(30)]);
+        assert_eq!(
+            filters("cadenas entre veinte y treinta caracteres", &SPANISH),
+            vec![SearchFilter::MinLength(20), SearchFilter::MaxLength(30)]
+        );
+        assert_eq!(filters("strings longer than twenty-one characters", &ENGLISH), vec![SearchFilter::MinLength(22)]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::query::filter::SearchFilter;
+use crate::query::tokenizer::tokenize;
+use serde_json::Value;
+
+/// A phrasing supplied through the rules file, e.g. the pattern
+/// `"{n:number} or more characters"` with filters `{ min_length = "{n}" }`.
+#[derive(Debug, Clone)]
+pub struct PhraseRule {
+    pub name: String,
+    pub pattern: Vec<PatternPart>,
+    filters: Vec<(String, FilterValue)>,
+}
+
+/// Values captured by a pattern's `{name}` placeholders, in pattern order.
+pub type Captures = Vec<(String, Value)>;
+
+#[derive(Debug, Clone)]
+pub enum PatternPart {
+    Word(String),
+    Capture { name: String, kind: CaptureKind },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureKind {
+    /// Digits or number words, as in the built-in grammar.
+    Number,
+    /// A single letter.
+    Letter,
+    /// Any single word.
+    Word,
+}
+
+#[derive(Debug, Clone)]
+enum FilterValue {
+    Literal(Value),
+    Capture(String),
+}
+
+/// On-disk shape of the rules file, in TOML (`[[rule]]` tables) or JSON
+/// (`{"rule": [...]}`).
+#[derive(serde::Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RawRule>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawRule {
+    name: String,
+    pattern: String,
+    filters: serde_json::Map<String, Value>,
+}
+
+/// Reads and validates a rules file. The format is chosen by extension:
+/// `.json` is JSON, anything else TOML.
+pub fn load_rules(path: &str) -> Result<Vec<PhraseRule>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+
+    let file: RulesFile = if path.ends_with(".json") {
+        serde_json::from_str(&contents).map_err(|e| format!("invalid JSON in {}: {}", path, e))?
+    } else {
+        toml::from_str(&contents).map_err(|e| format!("invalid TOML in {}: {}", path, e))?
+    };
+
+    file.rule.into_iter().map(compile_rule).collect()
+}
+
+fn compile_rule(raw: RawRule) -> Result<PhraseRule, String> {
+    let mut pattern = Vec::new();
+    for piece in raw.pattern.split_whitespace() {
+        if let Some(inner) = piece.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            let (name, kind) = inner.split_once(':').unwrap_or((inner, "word"));
+            let kind = match kind {
+                "number" => CaptureKind::Number,
+                "letter" => CaptureKind::Letter,
+                "word" => CaptureKind::Word,
+                other => return Err(format!("rule \"{}\": unknown capture type \"{}\"", raw.name, other)),
+            };
+            pattern.push(PatternPart::Capture { name: name.to_string(), kind });
+        } else {
+            // Literal words go through the query tokenizer so they compare equal to query tokens.
+            pattern.extend(tokenize(piece).into_iter().map(|token| PatternPart::Word(token.text)));
+        }
+    }
+    if pattern.is_empty() {
+        return Err(format!("rule \"{}\": pattern is empty", raw.name));
+    }
+
+    let mut filters = Vec::new();
+    for (key, value) in raw.filters {
+        let capture = value
+            .as_str()
+            .and_then(|v| v.strip_prefix('{'))
+            .and_then(|v| v.strip_suffix('}'))
+            .map(str::to_string);
+
+        let value = match capture {
+            Some(name) => {
+                let kind = pattern.iter().find_map(|part| match part {
+                    PatternPart::Capture { name: n, kind } if *n == name => Some(*kind),
+                    _ => None,
+                });
+                let Some(kind) = kind else {
+                    return Err(format!("rule \"{}\": filter \"{}\" uses unknown capture \"{}\"", raw.name, key, name));
+                };
+                // Check the filter accepts what the capture produces.
+                let sample = match kind {
+                    CaptureKind::Number => serde_json::json!(1),
+                    CaptureKind::Letter | CaptureKind::Word => serde_json::json!("a"),
+                };
+                SearchFilter::from_pair(&key, &sample).map_err(|e| format!("rule \"{}\": {}", raw.name, e))?;
+                FilterValue::Capture(name)
+            },
+            None => {
+                SearchFilter::from_pair(&key, &value).map_err(|e| format!("rule \"{}\": {}", raw.name, e))?;
+                FilterValue::Literal(value)
+            }
+        };
+        filters.push((key, value));
+    }
+    if filters.is_empty() {
+        return Err(format!("rule \"{}\": no filters given", raw.name));
+    }
+
+    Ok(PhraseRule {
+        name: raw.name,
+        pattern,
+        filters,
+    })
+}
+
+impl PhraseRule {
+    /// Builds the rule's filters from the values captured in the query.
+    pub fn filters(&self, captures: &Captures) -> Result<Vec<SearchFilter>, String> {
+        self.filters
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    FilterValue::Literal(value) => value,
+                    FilterValue::Capture(name) => captures
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, v)| v)
+                        .ok_or_else(|| format!("capture \"{}\" did not match", name))?,
+                };
+                SearchFilter::from_pair(key, value)
+            })
+            .collect()
+    }
+}
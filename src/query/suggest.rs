@@ -0,0 +1,126 @@
+use crate::query::lexicon::{Lexicon, Phrase};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuggestionKind {
+    /// A whole example query, or the rest of one, that continues the prefix.
+    Template,
+    /// The prefix with its last, partly typed phrase completed.
+    Vocabulary,
+}
+
+impl SuggestionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SuggestionKind::Template => "template",
+            SuggestionKind::Vocabulary => "vocabulary",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub text: String,
+    pub kind: SuggestionKind,
+}
+
+/// Completes a partly typed query from the language pack's example templates
+/// and vocabulary. Template continuations come first, as they are known to parse.
+pub fn suggest(prefix: &str, lexicon: &Lexicon, limit: usize) -> Vec<Suggestion> {
+    let lower = prefix.to_lowercase();
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut push = |text: String, kind: SuggestionKind| {
+        if text.to_lowercase() != lower && !suggestions.iter().any(|s| s.text == text) {
+            suggestions.push(Suggestion { text, kind });
+        }
+    };
+
+    let mut templates: Vec<&str> = lexicon.templates.to_vec();
+    templates.sort_by_key(|t| t.len());
+    for template in templates {
+        if let Some(rest) = continuation(&lower, template, true) {
+            push(format!("{}{}", prefix, rest), SuggestionKind::Template);
+        }
+    }
+
+    // Only the last, partly typed phrase is completed; a trailing space means
+    // the user has not started the next word yet.
+    if !lower.is_empty() && !lower.ends_with(char::is_whitespace) {
+        let mut phrases = vocabulary(lexicon);
+        phrases.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        for phrase in phrases {
+            if let Some(rest) = continuation(&lower, &phrase, false) {
+                push(format!("{}{}", prefix, rest), SuggestionKind::Vocabulary);
+            }
+        }
+    }
+
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Finds the longest tail of `prefix`, starting at a word boundary, that
+/// `text` contains at a word boundary (or starts with, unless `anywhere`),
+/// and returns what follows it in `text`.
+fn continuation<'a>(prefix: &str, text: &'a str, anywhere: bool) -> Option<&'a str> {
+    let lower_text = text.to_lowercase();
+    // Lowercasing can change byte lengths; only offer texts where it does not.
+    if lower_text.len() != text.len() {
+        return None;
+    }
+
+    let boundaries = std::iter::once(0).chain(prefix.char_indices().filter(|(_, c)| c.is_whitespace()).map(|(i, c)| i + c.len_utf8()));
+    for start in boundaries {
+        let tail = &prefix[start..];
+        if tail.trim().is_empty() {
+            continue;
+        }
+        let found = lower_text
+            .match_indices(tail)
+            .find(|(i, _)| {
+                if !anywhere || (start == 0 && *i == 0) {
+                    return *i == 0;
+                }
+                // Splicing a template onto a different beginning needs at least
+                // two words in common, or a lone "the" would pull in every
+                // template containing "the".
+                tail.trim().contains(char::is_whitespace) && (*i == 0 || lower_text[..*i].ends_with(char::is_whitespace))
+            });
+        if let Some((i, _)) = found {
+            let rest = &text[i + tail.len()..];
+            return (!rest.is_empty()).then_some(rest);
+        }
+    }
+
+    prefix.is_empty().then_some(text)
+}
+
+/// Every phrase of the pack worth completing to, joined into text.
+fn vocabulary(lexicon: &Lexicon) -> Vec<String> {
+    let join = |phrase: Phrase| phrase.join(" ");
+    let mut phrases: Vec<String> = Vec::new();
+
+    for list in [
+        lexicon.fillers,
+        lexicon.negations,
+        lexicon.palindrome_words,
+        lexicon.between,
+        lexicon.length_units,
+        lexicon.word_units,
+        lexicon.containment,
+        lexicon.negated_containment,
+        lexicon.letter_nouns,
+        lexicon.limit_words,
+    ] {
+        phrases.extend(list.iter().copied().map(join));
+    }
+    phrases.extend(lexicon.comparators.iter().map(|(p, _)| join(p)));
+    phrases.extend(lexicon.named_letters.iter().map(|(p, _)| join(p)));
+    phrases.extend(lexicon.superlatives.iter().map(|(p, _, _)| join(p)));
+    phrases.extend(lexicon.numbers.iter().map(|(p, _)| join(p)));
+
+    // Single letters complete to nothing useful.
+    phrases.retain(|p| p.chars().count() > 1);
+    phrases.sort();
+    phrases.dedup();
+    phrases
+}
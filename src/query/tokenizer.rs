@@ -0,0 +1,58 @@
+/// A lowercased word or number from a natural-language query, with the byte
+/// range it occupies in the original text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits a query into word and number tokens. Punctuation and hyphens
+/// separate tokens ("twenty-five", "non-palindromic"); apostrophes inside a
+/// word are kept ("don't"), except after a French elided article or pronoun
+/// ("d'un" is "d'" and "un").
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    // Start offset, text so far, and end offset of the last non-apostrophe character.
+    let mut current: Option<(usize, String, usize)> = None;
+
+    for (i, c) in query.char_indices() {
+        let is_apostrophe = c == '\'' || c == '\u{2019}';
+        let end = i + c.len_utf8();
+        let elides = is_apostrophe && query[end..].starts_with(char::is_alphabetic);
+
+        match &mut current {
+            Some((start, text, _)) if elides && (text.chars().count() == 1 || text == "qu") => {
+                tokens.push(Token {
+                    text: format!("{}'", text),
+                    start: *start,
+                    end,
+                });
+                current = None;
+            },
+            Some((_, text, _)) if is_apostrophe => text.push('\''),
+            Some((_, text, last)) if c.is_alphanumeric() => {
+                text.extend(c.to_lowercase());
+                *last = end;
+            },
+            None if c.is_alphanumeric() => current = Some((i, c.to_lowercase().collect(), end)),
+            Some(_) => push_token(&mut tokens, current.take().expect("checked above")),
+            None => {}
+        }
+    }
+
+    if let Some(word) = current {
+        push_token(&mut tokens, word);
+    }
+
+    tokens
+}
+
+fn push_token(tokens: &mut Vec<Token>, (start, text, end): (usize, String, usize)) {
+    // A trailing apostrophe is a closing quote rather than part of the word.
+    tokens.push(Token {
+        text: text.trim_end_matches('\'').to_string(),
+        start,
+        end,
+    });
+}
@@ -0,0 +1,220 @@
+use crate::AppState;
+use crate::config::config::{SharedDatabase, TempDatabase};
+use crate::config::wal::{WalOp, WalWriter};
+use crate::error::ApiError;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, delete, dev::Payload, get, http::Method, web};
+
+const MAX_COLLECTION_NAME_LENGTH: usize = 64;
+
+/// The string store a request operates on: the global one, or the named
+/// collection captured by the `{collection}` path segment.
+///
+/// A POST into an unknown collection gets an empty, unregistered store; the
+/// collection is only created once [`Store::write`] stores something in it.
+/// Any other request against an unknown collection is rejected with a 404.
+///
+/// A collection deleted after the store was resolved is gone for good, so
+/// changes go through [`Store::write`] or [`Store::lock_current`], which
+/// reject it instead of writing into the orphaned database.
+pub struct Store {
+    pub collection: Option<String>,
+    pub db: SharedDatabase,
+    /// Whether the collection does not exist yet.
+    pending: bool,
+}
+
+impl Store {
+    /// Runs `write` against the store, handing it the write-ahead log. For a
+    /// collection that does not exist yet, the collection is created only if
+    /// `write` succeeds and leaves something in the store, so failed requests
+    /// leave nothing behind, and its creation is logged ahead of the change.
+    pub fn write<T>(&self, data: &AppState, write: impl FnOnce(&mut TempDatabase, &mut WalWriter) -> Result<T, ApiError>) -> Result<T, ApiError> {
+        let mut log = data.env.wal_writer();
+        let Some(name) = self.collection.as_ref().filter(|_| self.pending) else {
+            let mut db = self.lock_current(data)?;
+            return write(&mut db, &mut log);
+        };
+
+        // Held throughout, so concurrent first writes all land in one collection.
+        let mut collections = data.env.collections.lock().expect("collections mutex poisoned");
+        let shared = match collections.get(name) {
+            Some(db) => db.clone(),
+            None => {
+                log.prepend(WalOp::CreateCollection { collection: name.clone() });
+                self.db.clone()
+            }
+        };
+
+        let (output, is_empty) = {
+            let mut db = shared.lock().expect("db mutex poisoned");
+            let output = write(&mut db, &mut log)?;
+            (output, db.processed_results.is_empty() && db.trash.is_empty())
+        };
+        if !is_empty && !collections.contains_key(name) {
+            println!("Creating collection: {}", name);
+            collections.insert(name.clone(), shared);
+        }
+
+        return Ok(output);
+    }
+
+    /// Looks up the store for `collection` as it is now, for connections that
+    /// outlive the request that resolved their store.
+    pub fn current(data: &AppState, collection: Option<&str>) -> Result<Store, ApiError> {
+        let Some(name) = collection else {
+            return Ok(Store {
+                collection: None,
+                db: data.env.db.clone(),
+                pending: false,
+            });
+        };
+
+        let collections = data.env.collections.lock().expect("collections mutex poisoned");
+        let db = collections.get(name).cloned().ok_or(ApiError::CollectionNotFound)?;
+        return Ok(Store {
+            collection: Some(name.to_string()),
+            db,
+            pending: false,
+        });
+    }
+
+    /// Locks the store to change it, failing with a 404 if its collection
+    /// was deleted, or deleted and recreated, since the request resolved it.
+    pub fn lock_current(&self, data: &AppState) -> Result<std::sync::MutexGuard<'_, TempDatabase>, ApiError> {
+        let Some(name) = &self.collection else {
+            return Ok(self.db.lock().expect("db mutex poisoned"));
+        };
+
+        let collections = data.env.collections.lock().expect("collections mutex poisoned");
+        if !collections.get(name).is_some_and(|db| std::sync::Arc::ptr_eq(db, &self.db)) {
+            return Err(ApiError::CollectionNotFound);
+        }
+        return Ok(self.db.lock().expect("db mutex poisoned"));
+    }
+}
+
+impl std::ops::Deref for Store {
+    type Target = std::sync::Mutex<TempDatabase>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.db
+    }
+}
+
+impl FromRequest for Store {
+    type Error = ApiError;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        std::future::ready(resolve_store(req))
+    }
+}
+
+fn resolve_store(req: &HttpRequest) -> Result<Store, ApiError> {
+    let data = req.app_data::<web::Data<AppState>>().expect("AppState is not registered");
+
+    let Some(name) = req.match_info().get("collection") else {
+        return Ok(Store {
+            collection: None,
+            db: data.env.db.clone(),
+            pending: false,
+        });
+    };
+
+    if !is_valid_collection_name(name) {
+        return Err(ApiError::InvalidRequest(format!(
+            "Collection names must be 1-{} characters of letters, digits, '-' or '_'",
+            MAX_COLLECTION_NAME_LENGTH
+        )));
+    }
+
+    let collections = data.env.collections.lock().expect("collections mutex poisoned");
+    let (db, pending) = match collections.get(name) {
+        Some(db) => (db.clone(), false),
+        None if req.method() == Method::POST => (std::sync::Arc::new(std::sync::Mutex::new(TempDatabase::new())), true),
+        None => {
+            return Err(ApiError::CollectionNotFound);
+        }
+    };
+
+    return Ok(Store {
+        collection: Some(name.to_string()),
+        db,
+        pending,
+    });
+}
+
+fn is_valid_collection_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_COLLECTION_NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn collection_stats(name: &str, db: &TempDatabase) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "count": db.processed_results.len(),
+        "palindrome_count": db.processed_results.iter().filter(|res| res.is_palindrome).count(),
+        "trashed_count": db.trash.len(),
+    })
+}
+
+#[get("/collections")]
+async fn list_collections(_data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let collections = _data.env.collections.lock().expect("collections mutex poisoned");
+
+    let mut names: Vec<&String> = collections.keys().collect();
+    names.sort();
+
+    let data_array: Vec<serde_json::Value> = names
+        .into_iter()
+        .map(|name| {
+            let db = collections[name].lock().expect("db mutex poisoned");
+            collection_stats(name, &db)
+        })
+        .collect();
+
+    let response = serde_json::json!({
+        "data": data_array,
+        "count": data_array.len(),
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+#[get("/collections/{collection}")]
+async fn get_collection(store: Store) -> Result<HttpResponse, ApiError> {
+    let name = store.collection.clone().unwrap_or_default();
+    let db = store.lock().expect("db mutex poisoned");
+
+    return Ok(HttpResponse::Ok().json(collection_stats(&name, &db)));
+}
+
+#[delete("/collections/{collection}")]
+async fn delete_collection(_data: web::Data<AppState>, path: web::Path<String>) -> Result<HttpResponse, ApiError> {
+    let name: String = path.into_inner();
+    println!("Received request to delete collection: {}", name);
+
+    let removed = {
+        let mut log = _data.env.wal_writer();
+        let mut collections = _data.env.collections.lock().expect("collections mutex poisoned");
+        if collections.contains_key(&name) {
+            log.append(&[WalOp::DropCollection { collection: name.clone() }])?;
+        }
+        collections.remove(&name)
+    };
+    _data.env.wal_sync().await?;
+
+    match removed {
+        Some(_) => {
+            let json_response = serde_json::json!({
+                "status": "success",
+                "message": "Collection successfully deleted from the system",
+            });
+            return Ok(HttpResponse::Ok().json(json_response));
+        },
+        None => {
+            return Err(ApiError::CollectionNotFound);
+        }
+    }
+}
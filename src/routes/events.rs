@@ -0,0 +1,136 @@
+use crate::AppState;
+use crate::config::events::StoreEvent;
+use crate::error::ApiError;
+use crate::query::filter::SearchFilter;
+use crate::routes::collections::Store;
+use crate::routes::strings::{StringQuery, extract_context_filters, extract_filters_from_query, matches_filters, result_to_json, validate_filters};
+use crate::routes::params::Query;
+use actix_web::{HttpRequest, HttpResponse, get, http::header, web, web::Bytes};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+
+/// Comment lines keep idle connections from being closed by proxies.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(serde::Deserialize, Debug)]
+struct EventsQuery {
+    last_event_id: Option<u64>,
+}
+
+/// One client's change feed: events not yet written out, and what it has seen so far.
+struct Subscription {
+    data: web::Data<AppState>,
+    receiver: Receiver<Arc<StoreEvent>>,
+    collection: Option<String>,
+    filters: Vec<SearchFilter>,
+    last_id: u64,
+    pending: VecDeque<Bytes>,
+    keep_alive: tokio::time::Interval,
+}
+
+/// Streams `created` and `deleted` events as Server-Sent Events. The list
+/// endpoint's filters narrow the feed; `Last-Event-ID` (or `last_event_id`)
+/// replays retained events after that id before going live.
+#[get("/strings/events")]
+async fn string_events(_data: web::Data<AppState>, store: Store, req: HttpRequest, query: Query<StringQuery>, events_query: Query<EventsQuery>) -> Result<HttpResponse, ApiError> {
+    let mut filters = extract_filters_from_query(&query.into_inner());
+    filters.extend(extract_context_filters(req.query_string()));
+    validate_filters(&filters)?;
+
+    let last_event_id = match req.headers().get("Last-Event-ID") {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .ok_or_else(|| ApiError::InvalidRequest("Last-Event-ID must be a non-negative integer".to_string()))?,
+        ),
+        None => events_query.into_inner().last_event_id,
+    };
+
+    println!("New change feed subscriber, resuming after {:?}", last_event_id);
+
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    keep_alive.reset();
+
+    // Subscribe before reading the log so nothing published in between is missed.
+    let mut subscription = Subscription {
+        receiver: _data.env.events.subscribe(),
+        data: _data,
+        collection: store.collection.clone(),
+        filters,
+        last_id: 0,
+        pending: VecDeque::from([Bytes::from_static(b"retry: 3000\n\n")]),
+        keep_alive,
+    };
+    if let Some(last_event_id) = last_event_id {
+        subscription.last_id = last_event_id;
+        subscription.catch_up();
+    }
+
+    let stream = futures_util::stream::unfold(subscription, |mut subscription| async move {
+        let chunk = subscription.next_chunk().await?;
+        Some((Ok::<Bytes, actix_web::Error>(chunk), subscription))
+    });
+
+    return Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream));
+}
+
+impl Subscription {
+    async fn next_chunk(&mut self) -> Option<Bytes> {
+        loop {
+            if let Some(chunk) = self.pending.pop_front() {
+                return Some(chunk);
+            }
+
+            tokio::select! {
+                received = self.receiver.recv() => match received {
+                    Ok(event) => self.queue(&event),
+                    Err(RecvError::Lagged(_)) => self.catch_up(),
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = self.keep_alive.tick() => {
+                    self.pending.push_back(Bytes::from_static(b": keep-alive\n\n"));
+                }
+            }
+        }
+    }
+
+    /// Queues the retained events after `last_id`, announcing a `reset` when
+    /// some of them have already been dropped from the log.
+    fn catch_up(&mut self) {
+        let (events, missed) = self.data.env.events.since(self.last_id);
+        if missed {
+            let data = serde_json::json!({
+                "detail": format!("Events after id {} are no longer available; reload the strings list", self.last_id),
+            });
+            self.pending.push_back(Bytes::from(format!("event: reset\ndata: {}\n\n", data)));
+            // The id may be from before a restart and larger than any new one.
+            self.last_id = 0;
+        }
+        for event in events {
+            self.queue(&event);
+        }
+    }
+
+    fn queue(&mut self, event: &StoreEvent) {
+        // Events replayed from the log may also arrive on the channel.
+        if event.id <= self.last_id {
+            return;
+        }
+        self.last_id = event.id;
+
+        if event.collection != self.collection || !matches_filters(&event.result, &self.filters) {
+            return;
+        }
+
+        let mut data = result_to_json(&event.result);
+        data["event_at"] = serde_json::json!(event.at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+        self.pending.push_back(Bytes::from(format!("id: {}\nevent: {}\ndata: {}\n\n", event.id, event.kind.as_str(), data)));
+    }
+}
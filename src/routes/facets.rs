@@ -0,0 +1,144 @@
+use crate::config::config::AnalysisResult;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FacetField {
+    IsPalindrome,
+    Length,
+    WordCount,
+    UniqueCharacters,
+    Tag,
+}
+
+/// One entry of `?facets=`: a field, optionally bucketed into a histogram.
+#[derive(Debug, Clone)]
+pub(crate) struct FacetSpec {
+    field: FacetField,
+    bucket: Option<usize>,
+}
+
+impl FacetField {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "is_palindrome" => Some(FacetField::IsPalindrome),
+            "length" => Some(FacetField::Length),
+            "word_count" => Some(FacetField::WordCount),
+            "unique_characters" => Some(FacetField::UniqueCharacters),
+            "tag" => Some(FacetField::Tag),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FacetField::IsPalindrome => "is_palindrome",
+            FacetField::Length => "length",
+            FacetField::WordCount => "word_count",
+            FacetField::UniqueCharacters => "unique_characters",
+            FacetField::Tag => "tag",
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        !matches!(self, FacetField::IsPalindrome | FacetField::Tag)
+    }
+
+    fn numeric_value(&self, result: &AnalysisResult) -> Option<usize> {
+        match self {
+            FacetField::Length => Some(result.length),
+            FacetField::WordCount => Some(result.word_count),
+            FacetField::UniqueCharacters => Some(result.unique_characters),
+            FacetField::IsPalindrome | FacetField::Tag => None,
+        }
+    }
+}
+
+/// Parses a facet list such as `is_palindrome,word_count,length:bucket=5`.
+pub(crate) fn parse_facets(raw: &str) -> Result<Vec<FacetSpec>, String> {
+    let mut specs = Vec::new();
+
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, option) = match part.split_once(':') {
+            Some((name, option)) => (name, Some(option)),
+            None => (part, None),
+        };
+
+        let field = FacetField::parse(name).ok_or_else(|| format!("Unknown facet field \"{}\"", name))?;
+
+        let bucket = match option {
+            None => None,
+            Some(option) => {
+                let size = option
+                    .strip_prefix("bucket=")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .filter(|size| *size > 0)
+                    .ok_or_else(|| format!("Invalid facet option \"{}\", expected bucket=<positive integer>", option))?;
+                if !field.is_numeric() {
+                    return Err(format!("Facet field \"{}\" cannot be bucketed", name));
+                }
+                Some(size)
+            }
+        };
+
+        specs.push(FacetSpec { field, bucket });
+    }
+
+    Ok(specs)
+}
+
+pub(crate) fn compute_facets(results: &[AnalysisResult], specs: &[FacetSpec]) -> serde_json::Value {
+    let mut facets = serde_json::Map::new();
+
+    for spec in specs {
+        let facet = match (spec.field, spec.bucket) {
+            (field, Some(size)) => histogram(results.iter().filter_map(|res| field.numeric_value(res)), size),
+            (FacetField::IsPalindrome, None) => value_counts(results.iter().map(|res| res.is_palindrome.to_string())),
+            (FacetField::Tag, None) => value_counts(results.iter().flat_map(|res| res.tags.iter().cloned())),
+            (field, None) => {
+                let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+                for value in results.iter().filter_map(|res| field.numeric_value(res)) {
+                    *counts.entry(value).or_insert(0) += 1;
+                }
+                serde_json::json!(counts)
+            }
+        };
+        facets.insert(spec.field.name().to_string(), facet);
+    }
+
+    serde_json::Value::Object(facets)
+}
+
+fn value_counts(values: impl Iterator<Item = String>) -> serde_json::Value {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    serde_json::json!(counts)
+}
+
+/// Buckets values into `[from, to]` ranges of `size`, including the empty
+/// buckets between the smallest and largest value so charts have no gaps.
+fn histogram(values: impl Iterator<Item = usize>, size: usize) -> serde_json::Value {
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value / size).or_insert(0) += 1;
+    }
+
+    let buckets: Vec<serde_json::Value> = match (counts.keys().next(), counts.keys().next_back()) {
+        (Some(&first), Some(&last)) => (first..=last)
+            .map(|bucket| {
+                serde_json::json!({
+                    "from": bucket * size,
+                    "to": bucket * size + size - 1,
+                    "count": counts.get(&bucket).copied().unwrap_or(0),
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    serde_json::json!({
+        "bucket_size": size,
+        "buckets": buckets,
+    })
+}
@@ -0,0 +1,186 @@
+use crate::error::ApiError;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, dev::Payload, http::header, web::Bytes};
+use serde_json::Value;
+
+/// Representation of a list response, chosen from the `Accept` header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseFormat {
+    Json,
+    Csv,
+    Ndjson,
+    MsgPack,
+}
+
+const CSV_COLUMNS: &[&str] = &["id", "value", "length", "is_palindrome", "unique_characters", "word_count", "created_at", "tags", "metadata"];
+
+impl ResponseFormat {
+    fn from_mime(mime: &actix_web::mime::Mime) -> Option<ResponseFormat> {
+        match (mime.type_().as_str(), mime.subtype().as_str()) {
+            ("*", "*") | ("application", "*") | ("application", "json") => Some(ResponseFormat::Json),
+            ("text", "*") | ("text", "csv") => Some(ResponseFormat::Csv),
+            ("application", "x-ndjson") | ("application", "ndjson") => Some(ResponseFormat::Ndjson),
+            ("application", "msgpack") | ("application", "x-msgpack") => Some(ResponseFormat::MsgPack),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "application/json",
+            ResponseFormat::Csv => "text/csv; charset=utf-8",
+            ResponseFormat::Ndjson => "application/x-ndjson",
+            ResponseFormat::MsgPack => "application/msgpack",
+        }
+    }
+
+    /// Renders a list response. JSON and MessagePack carry the whole body;
+    /// CSV and NDJSON carry only the `data` rows, with the row count in
+    /// `X-Total-Count`.
+    pub fn respond(self, body: Value) -> Result<HttpResponse, ApiError> {
+        let mut builder = HttpResponse::Ok();
+        builder
+            .insert_header((header::CONTENT_TYPE, self.content_type()))
+            .insert_header((header::VARY, "Accept"));
+
+        match self {
+            ResponseFormat::Json => Ok(builder.json(body)),
+            ResponseFormat::MsgPack => {
+                let bytes = rmp_serde::to_vec_named(&body).map_err(|e| ApiError::Internal(format!("Could not encode MessagePack: {}", e)))?;
+                Ok(builder.body(bytes))
+            },
+            ResponseFormat::Csv => {
+                let rows = data_rows(body);
+                builder.insert_header(("X-Total-Count", rows.len()));
+                Ok(builder.body(rows_to_csv(&rows)?))
+            },
+            ResponseFormat::Ndjson => {
+                let rows = data_rows(body);
+                builder.insert_header(("X-Total-Count", rows.len()));
+                let lines = rows.into_iter().map(|row| Ok::<Bytes, actix_web::Error>(Bytes::from(format!("{}\n", row))));
+                Ok(builder.streaming(futures_util::stream::iter(lines)))
+            },
+        }
+    }
+}
+
+impl FromRequest for ResponseFormat {
+    type Error = ApiError;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        std::future::ready(negotiate(req))
+    }
+}
+
+fn negotiate(req: &HttpRequest) -> Result<ResponseFormat, ApiError> {
+    if !req.headers().contains_key(header::ACCEPT) {
+        return Ok(ResponseFormat::Json);
+    }
+    // An unparsable header is treated like a missing one.
+    let Ok(accept) = <header::Accept as header::Header>::parse(req) else {
+        return Ok(ResponseFormat::Json);
+    };
+
+    let acceptable: Vec<_> = accept.iter().filter(|item| item.quality > header::Quality::ZERO).cloned().collect();
+    if acceptable.is_empty() {
+        return Ok(ResponseFormat::Json);
+    }
+
+    header::Accept(acceptable)
+        .ranked()
+        .iter()
+        .find_map(ResponseFormat::from_mime)
+        .ok_or(ApiError::NotAcceptable)
+}
+
+fn data_rows(mut body: Value) -> Vec<Value> {
+    match body["data"].take() {
+        Value::Array(rows) => rows,
+        _ => Vec::new(),
+    }
+}
+
+/// One row per result; tags are `;`-separated and metadata is a JSON object.
+fn rows_to_csv(rows: &[Value]) -> Result<Vec<u8>, ApiError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_error = |e: csv::Error| ApiError::Internal(format!("Could not encode CSV: {}", e));
+
+    writer.write_record(CSV_COLUMNS).map_err(csv_error)?;
+    for row in rows {
+        let properties = &row["properties"];
+        let tags: Vec<&str> = row["tags"].as_array().map(|tags| tags.iter().filter_map(Value::as_str).collect()).unwrap_or_default();
+        let record = [
+            plain(&row["id"]),
+            plain(&row["value"]),
+            plain(&properties["length"]),
+            plain(&properties["is_palindrome"]),
+            plain(&properties["unique_characters"]),
+            plain(&properties["word_count"]),
+            plain(&row["created_at"]),
+            tags.join(";"),
+            row["metadata"].to_string(),
+        ];
+        writer.write_record(record.map(spreadsheet_safe)).map_err(csv_error)?;
+    }
+
+    writer.into_inner().map_err(|e| ApiError::Internal(format!("Could not encode CSV: {}", e)))
+}
+
+/// Prefixes cells a spreadsheet would run as a formula with `'`, so a stored
+/// `=HYPERLINK(...)` is shown as text instead.
+fn spreadsheet_safe(cell: String) -> String {
+    if cell.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        return format!("'{}", cell);
+    }
+    return cell;
+}
+
+/// Strings without their JSON quotes, everything else as JSON text.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(value: &str, tags: &[&str]) -> Value {
+        serde_json::json!({
+            "id": "abc",
+            "value": value,
+            "properties": {"length": 1, "is_palindrome": true, "unique_characters": 1, "word_count": 1},
+            "created_at": "2025-01-01T00:00:00.000Z",
+            "tags": tags,
+            "metadata": {},
+        })
+    }
+
+    fn csv_lines(rows: &[Value]) -> Vec<String> {
+        String::from_utf8(rows_to_csv(rows).unwrap()).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn formula_cells_are_escaped() {
+        for value in ["=HYPERLINK(\"http://x\")", "+1", "-1", "@SUM(A1)", "\tx"] {
+            let lines = csv_lines(&[row(value, &[])]);
+            let cell = lines[1].split(',').nth(1).unwrap().trim_matches('"').to_string();
+            assert!(cell.starts_with('\''), "{} was written as {}", value, cell);
+        }
+    }
+
+    #[test]
+    fn formula_tags_are_escaped() {
+        let lines = csv_lines(&[row("plain", &["=cmd", "ok"])]);
+        assert!(lines[1].contains("'=cmd;ok"));
+    }
+
+    #[test]
+    fn ordinary_cells_are_unchanged() {
+        let lines = csv_lines(&[row("hello", &["a"])]);
+        assert_eq!(lines[1], "abc,hello,1,true,1,1,2025-01-01T00:00:00.000Z,a,{}");
+    }
+}
@@ -1,3 +1,13 @@
+pub mod collections;
+pub mod events;
+pub mod facets;
+pub mod format;
 pub mod healthz;
 pub mod me;
-pub mod strings;
\ No newline at end of file
+pub mod params;
+pub mod socket;
+pub mod stats;
+pub mod strings;
+pub mod transfer;
+pub mod trash;
+pub mod webhooks;
\ No newline at end of file
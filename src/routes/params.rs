@@ -0,0 +1,106 @@
+use crate::error::{ApiError, serde_error_to_api_error};
+use actix_web::{FromRequest, HttpRequest, dev::Payload, error::QueryPayloadError, web};
+use serde::de::DeserializeOwned;
+
+/// Query string extractor like `web::Query`, but a value that fails to
+/// deserialize is reported as a 422 naming the parameter.
+#[derive(Debug)]
+pub struct Query<T>(pub T);
+
+impl<T> Query<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    type Error = ApiError;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        std::future::ready(parse_query(req.query_string()))
+    }
+}
+
+fn parse_query<T: DeserializeOwned>(query_string: &str) -> Result<Query<T>, ApiError> {
+    match deserialize::<T>(query_string) {
+        Ok(value) => Ok(Query(value)),
+        Err(message) => Err(serde_error_to_api_error(&message, find_invalid_param::<T>(query_string))),
+    }
+}
+
+/// Deserializes `query_string`, returning serde's own message on failure.
+fn deserialize<T: DeserializeOwned>(query_string: &str) -> Result<T, String> {
+    match web::Query::<T>::from_query(query_string) {
+        Ok(query) => Ok(query.into_inner()),
+        Err(QueryPayloadError::Deserialize(e)) => Err(e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// serde_urlencoded's errors ("invalid digit found in string") omit the
+/// parameter, so each pair is deserialized into `T` on its own to find the
+/// one it rejects. A pair alone may lack other required fields, which is
+/// not its fault.
+fn find_invalid_param<T: DeserializeOwned>(query_string: &str) -> Option<String> {
+    query_string.split('&').filter(|pair| !pair.is_empty()).find_map(|pair| {
+        let message = deserialize::<T>(pair).err()?;
+        if message.starts_with("missing field ") {
+            return None;
+        }
+        let (key, _) = web::Query::<Vec<(String, String)>>::from_query(pair).ok()?.into_inner().into_iter().next()?;
+        Some(key)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Search {
+        q: String,
+        limit: Option<u64>,
+        exact: Option<bool>,
+    }
+
+    fn field_of(query_string: &str) -> Option<String> {
+        match parse_query::<Search>(query_string) {
+            Err(ApiError::InvalidField { field, .. }) => field,
+            other => panic!("expected an invalid field, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn names_the_rejected_parameter() {
+        assert_eq!(field_of("q=abc&limit=ten").as_deref(), Some("limit"));
+        assert_eq!(field_of("limit=5&exact=maybe&q=abc").as_deref(), Some("exact"));
+        assert_eq!(field_of("exact=true&limit=-1").as_deref(), Some("limit"));
+    }
+
+    #[test]
+    fn decodes_the_parameter_name() {
+        assert_eq!(field_of("q=abc&%6Cimit=x").as_deref(), Some("limit"));
+    }
+
+    #[test]
+    fn reports_a_missing_field() {
+        assert!(matches!(parse_query::<Search>("limit=5"), Err(ApiError::MissingField(field)) if field == "q"));
+    }
+
+    #[test]
+    fn accepts_a_valid_query() {
+        let query = parse_query::<Search>("q=abc&limit=5").unwrap();
+        assert_eq!(query.q, "abc");
+        assert_eq!(query.limit, Some(5));
+        assert_eq!(query.exact, None);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::AppState;
+use crate::error::ApiError;
+use crate::routes::collections::Store;
+use crate::routes::strings::{UserInput, analyse_string, result_to_json, store_string, struct_to_json};
+use crate::routes::params::Query;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use actix_ws::{AggregatedMessage, Session};
+use serde_json::Value;
+use tokio::time::{Duration, Instant};
+
+const MAX_DEBOUNCE_MS: u64 = 10_000;
+/// Largest text message accepted, after joining continuation frames.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+#[derive(serde::Deserialize, Debug)]
+struct SocketOptions {
+    #[serde(default)]
+    debounce_ms: u64,
+}
+
+/// A JSON text frame. Any other text frame is analysed as-is.
+#[derive(serde::Deserialize)]
+struct SocketMessage {
+    #[serde(default)]
+    store: bool,
+    #[serde(flatten)]
+    input: UserInput,
+}
+
+/// Live analysis over a WebSocket. Each text frame is answered with its
+/// analysis, without storing it; a `{"value": ..., "store": true}` message
+/// stores the string exactly like `POST /strings`. With `debounce_ms`, only
+/// the last of a burst of frames is analysed, once the client pauses.
+#[get("/ws/analyse")]
+async fn analyse_socket(_data: web::Data<AppState>, store: Store, req: HttpRequest, body: web::Payload, options: Query<SocketOptions>) -> Result<HttpResponse, ApiError> {
+    let debounce_ms = options.into_inner().debounce_ms;
+    if debounce_ms > MAX_DEBOUNCE_MS {
+        return Err(ApiError::InvalidField {
+            field: Some("debounce_ms".to_string()),
+            detail: format!("must be at most {}", MAX_DEBOUNCE_MS),
+        });
+    }
+
+    let (response, session, messages) = actix_ws::handle(&req, body).map_err(|e| ApiError::InvalidRequest(format!("WebSocket handshake failed: {}", e)))?;
+    let messages = messages.aggregate_continuations().max_continuation_size(MAX_MESSAGE_SIZE);
+
+    println!("Analysis socket opened with debounce of {}ms", debounce_ms);
+    actix_web::rt::spawn(run_socket(_data, store.collection.clone(), session, messages, Duration::from_millis(debounce_ms)));
+
+    return Ok(response);
+}
+
+/// `collection` is resolved again for every stored message, as it may be
+/// deleted while the socket is open.
+async fn run_socket(data: web::Data<AppState>, collection: Option<String>, mut session: Session, mut messages: actix_ws::AggregatedMessageStream, debounce: Duration) {
+    // Text waiting for the debounce period to pass without a newer frame.
+    let mut pending: Option<(String, Instant)> = None;
+
+    loop {
+        let deadline = pending.as_ref().map(|(_, deadline)| *deadline);
+        let message = tokio::select! {
+            message = messages.recv() => message,
+            _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                let (text, _) = pending.take().expect("checked above");
+                if session.text(analysis_message(text).to_string()).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let reply = match message {
+            Some(Ok(AggregatedMessage::Text(text))) => match parse_message(&text) {
+                Ok(message) if message.store => Some(stored_message(&data, collection.as_deref(), message.input).await),
+                Ok(message) if !debounce.is_zero() => {
+                    pending = Some((message.input.value, Instant::now() + debounce));
+                    None
+                },
+                Ok(message) => Some(analysis_message(message.input.value)),
+                Err(e) => Some(error_message(&e)),
+            },
+            Some(Ok(AggregatedMessage::Binary(_))) => Some(error_message(&ApiError::InvalidRequest("Only text frames are supported".to_string()))),
+            Some(Ok(AggregatedMessage::Ping(bytes))) => {
+                if session.pong(&bytes).await.is_err() {
+                    return;
+                }
+                None
+            },
+            Some(Ok(AggregatedMessage::Pong(_))) => None,
+            Some(Ok(AggregatedMessage::Close(_))) | Some(Err(_)) | None => break,
+        };
+
+        if let Some(reply) = reply && session.text(reply.to_string()).await.is_err() {
+            return;
+        }
+    }
+
+    let _ = session.close(None).await;
+}
+
+/// Reads a frame as a JSON message when it is a JSON object, or as plain text otherwise.
+fn parse_message(text: &str) -> Result<SocketMessage, ApiError> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(value @ Value::Object(_)) => {
+            if !value.get("value").is_some_and(Value::is_string) {
+                return Err(ApiError::MissingField("value".to_string()));
+            }
+            serde_json::from_value(value).map_err(|e| ApiError::InvalidRequest(format!("Invalid message: {}", e)))
+        },
+        _ => Ok(SocketMessage {
+            store: false,
+            input: UserInput {
+                value: text.to_string(),
+                tags: Vec::new(),
+                metadata: serde_json::Map::new(),
+            },
+        }),
+    }
+}
+
+fn analysis_message(value: String) -> Value {
+    let result = analyse_string(value);
+    serde_json::json!({
+        "type": "analysis",
+        "id": result.sha256_hash,
+        "value": result.word,
+        "properties": struct_to_json(&result),
+    })
+}
+
+async fn stored_message(data: &AppState, collection: Option<&str>, input: UserInput) -> Value {
+    let store = match Store::current(data, collection) {
+        Ok(store) => store,
+        Err(e) => {
+            return error_message(&e);
+        }
+    };
+
+    match store_string(data, &store, input).await {
+        Ok(result) => serde_json::json!({
+            "type": "stored",
+            "data": result_to_json(&result),
+        }),
+        Err(e) => error_message(&e),
+    }
+}
+
+fn error_message(error: &ApiError) -> Value {
+    serde_json::json!({
+        "type": "error",
+        "problem": error.problem_json(None),
+    })
+}
@@ -0,0 +1,222 @@
+use crate::config::config::AnalysisResult;
+use crate::error::ApiError;
+use crate::routes::collections::Store;
+use crate::routes::strings::{StringQuery, apply_filters, enum_to_string, extract_context_filters, extract_filters_from_query, validate_filters};
+use crate::routes::params::Query;
+use actix_web::{HttpRequest, HttpResponse, get};
+
+const PERCENTILES: [usize; 5] = [25, 75, 90, 95, 99];
+
+#[get("/stats")]
+async fn get_stats(store: Store, req: HttpRequest, query: Query<StringQuery>) -> Result<HttpResponse, ApiError> {
+    let q = query.into_inner();
+    println!("Received query for stats: {:?}", q);
+
+    let mut selected_filters = extract_filters_from_query(&q);
+    selected_filters.extend(extract_context_filters(req.query_string()));
+    validate_filters(&selected_filters)?;
+
+    let results = apply_filters(&store, selected_filters.clone());
+
+    let mut response = corpus_stats(&results);
+    response["filters_applied"] = enum_to_string(selected_filters);
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+fn corpus_stats(results: &[AnalysisResult]) -> serde_json::Value {
+    let count = results.len();
+    let palindrome_count = results.iter().filter(|res| res.is_palindrome).count();
+    let palindrome_ratio = if count == 0 { None } else { Some(palindrome_count as f64 / count as f64) };
+
+    let mut character_frequency: std::collections::BTreeMap<char, usize> = std::collections::BTreeMap::new();
+    for result in results {
+        for (c, n) in result.character_frequency_map.iter() {
+            *character_frequency.entry(*c).or_insert(0) += n;
+        }
+    }
+
+    serde_json::json!({
+        "count": count,
+        "palindrome_count": palindrome_count,
+        "palindrome_ratio": palindrome_ratio,
+        "length": distribution(results.iter().map(|res| res.length).collect()),
+        "word_count": distribution(results.iter().map(|res| res.word_count).collect()),
+        "unique_characters": distribution(results.iter().map(|res| res.unique_characters).collect()),
+        "character_frequency": character_frequency,
+    })
+}
+
+/// Summarises a set of values; every field is null for an empty set.
+fn distribution(mut values: Vec<usize>) -> serde_json::Value {
+    if values.is_empty() {
+        return serde_json::json!({
+            "min": null,
+            "max": null,
+            "mean": null,
+            "median": null,
+            "percentiles": null,
+        });
+    }
+
+    values.sort_unstable();
+    let len = values.len();
+    let mean = values.iter().sum::<usize>() as f64 / len as f64;
+    let median = if len.is_multiple_of(2) {
+        (values[len / 2 - 1] + values[len / 2]) as f64 / 2.0
+    } else {
+        values[len / 2] as f64
+    };
+
+    let mut percentiles = serde_json::Map::new();
+    for p in PERCENTILES {
+        percentiles.insert(format!("p{}", p), serde_json::json!(nearest_rank(&values, p)));
+    }
+
+    serde_json::json!({
+        "min": values[0],
+        "max": values[len - 1],
+        "mean": mean,
+        "median": median,
+        "percentiles": percentiles,
+    })
+}
+
+/// Nearest-rank percentile of an already sorted, non-empty slice.
+fn nearest_rank(sorted: &[usize], percentile: usize) -> usize {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    return sorted[rank - 1];
+}
+
+const MAX_TIMELINE_BUCKETS: i64 = 10_000;
+
+#[derive(serde::Deserialize, Debug)]
+struct TimelineQuery {
+    interval: Option<String>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[get("/stats/timeline")]
+async fn get_stats_timeline(store: Store, query: Query<TimelineQuery>) -> Result<HttpResponse, ApiError> {
+    let q = query.into_inner();
+    println!("Received query for timeline: {:?}", q);
+
+    let interval_name = q.interval.unwrap_or_else(|| "day".to_string());
+    let interval = match interval_name.as_str() {
+        "hour" => chrono::TimeDelta::hours(1),
+        "day" => chrono::TimeDelta::days(1),
+        _ => {
+            return Err(ApiError::InvalidRequest("interval must be one of: hour, day".to_string()));
+        }
+    };
+
+    if let (Some(from), Some(to)) = (q.from, q.to) && from > to {
+        return Err(ApiError::InvalidRequest("from must not be later than to".to_string()));
+    }
+
+    // (count, palindrome_count) keyed by the start of each bucket.
+    let mut buckets: std::collections::BTreeMap<chrono::DateTime<chrono::Utc>, (usize, usize)> = std::collections::BTreeMap::new();
+    {
+        let db = store.lock().expect("db mutex poisoned");
+        for result in db.processed_results.iter() {
+            if q.from.is_some_and(|from| result.created_at < from) || q.to.is_some_and(|to| result.created_at > to) {
+                continue;
+            }
+            // Only timestamps at chrono's outer limits have no bucket.
+            let Some(start) = bucket_start(result.created_at, interval) else {
+                continue;
+            };
+            let entry = buckets.entry(start).or_insert((0, 0));
+            entry.0 += 1;
+            if result.is_palindrome {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let first = match q.from {
+        Some(from) => Some(bucket_start(from, interval).ok_or_else(|| out_of_range("from"))?),
+        None => buckets.keys().next().copied(),
+    };
+    let last = match q.to {
+        Some(to) => Some(bucket_start(to, interval).ok_or_else(|| out_of_range("to"))?),
+        None => buckets.keys().next_back().copied(),
+    };
+
+    let mut data_array: Vec<serde_json::Value> = Vec::new();
+    if let (Some(first), Some(last)) = (first, last) {
+        if (last - first).num_seconds() / interval.num_seconds() >= MAX_TIMELINE_BUCKETS {
+            return Err(ApiError::InvalidRequest(format!(
+                "Timeline spans more than {} buckets, narrow from/to or use a larger interval",
+                MAX_TIMELINE_BUCKETS
+            )));
+        }
+
+        let mut start = first;
+        while start <= last {
+            let (count, palindrome_count) = buckets.get(&start).copied().unwrap_or((0, 0));
+            data_array.push(serde_json::json!({
+                "bucket_start": start.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                "count": count,
+                "palindrome_count": palindrome_count,
+            }));
+            match start.checked_add_signed(interval) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+    }
+
+    let response = serde_json::json!({
+        "interval": interval_name,
+        "from": q.from,
+        "to": q.to,
+        "total": buckets.values().map(|(count, _)| count).sum::<usize>(),
+        "data": data_array,
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+fn out_of_range(field: &str) -> ApiError {
+    ApiError::InvalidField {
+        field: Some(field.to_string()),
+        detail: "is outside the supported date range".to_string(),
+    }
+}
+
+/// Start of the bucket holding `timestamp`, or `None` when that start is not
+/// a representable date.
+fn bucket_start(timestamp: chrono::DateTime<chrono::Utc>, interval: chrono::TimeDelta) -> Option<chrono::DateTime<chrono::Utc>> {
+    let seconds = interval.num_seconds();
+    let start = timestamp.timestamp().div_euclid(seconds).checked_mul(seconds)?;
+    return chrono::DateTime::from_timestamp(start, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap().to_utc()
+    }
+
+    #[test]
+    fn bucket_start_truncates_to_the_interval() {
+        assert_eq!(bucket_start(at("2025-03-04T05:06:07Z"), chrono::TimeDelta::hours(1)), Some(at("2025-03-04T05:00:00Z")));
+        assert_eq!(bucket_start(at("2025-03-04T05:06:07Z"), chrono::TimeDelta::days(1)), Some(at("2025-03-04T00:00:00Z")));
+    }
+
+    #[test]
+    fn bucket_start_handles_dates_outside_the_nanosecond_range() {
+        assert_eq!(bucket_start(at("1500-01-01T12:30:00Z"), chrono::TimeDelta::days(1)), Some(at("1500-01-01T00:00:00Z")));
+        assert_eq!(bucket_start(at("9999-12-31T23:59:59Z"), chrono::TimeDelta::hours(1)), Some(at("9999-12-31T23:00:00Z")));
+    }
+
+    #[test]
+    fn bucket_start_at_chrono_limits_does_not_panic() {
+        let _ = bucket_start(chrono::DateTime::<chrono::Utc>::MIN_UTC, chrono::TimeDelta::days(1));
+        let _ = bucket_start(chrono::DateTime::<chrono::Utc>::MAX_UTC, chrono::TimeDelta::days(1));
+    }
+}
@@ -1,32 +1,52 @@
 use crate::AppState;
+use crate::config::api_version::ApiVersion;
+use crate::error::ApiError;
+use crate::query::filter::SearchFilter;
+use crate::query::lexicon::{LANGUAGE_PACKS, Lexicon, detect_language, language_pack};
+use crate::query::parser::{ParsedQuery, parse};
+use crate::query::suggest::suggest;
+use crate::routes::collections::Store;
+use crate::routes::format::ResponseFormat;
+use crate::routes::facets::{FacetSpec, compute_facets, parse_facets};
 use crate::config::config::{AnalysisResult, TempDatabase};
-use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use crate::config::events::EventKind;
+use crate::config::wal::WalOp;
+use crate::routes::params::Query;
+use actix_web::{HttpRequest, HttpResponse, delete, get, http::header, patch, post, web};
+use futures_util::StreamExt;
 use serde_json::Value;
 use sha2::{Sha256, Digest};
 
 #[derive(serde::Deserialize)]
-struct UserInput {
-    value: String,
+pub(crate) struct UserInput {
+    pub(crate) value: String,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) metadata: serde_json::Map<String, Value>,
 }
 
 #[derive(serde::Deserialize)]
+struct ContextUpdate {
+    tags: Option<Vec<String>>,
+    metadata: Option<serde_json::Map<String, Value>>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BatchItem {
+    Plain(String),
+    Input(UserInput),
+}
+
 enum ProcessStringError {
     EmptyInput,
     NotFound,
     Found(AnalysisResult),
 }
 
-#[derive(serde::Deserialize, Debug, Clone)]
-enum SearchFilter {
-    IsPalindrome(bool),
-    MinLength(usize),
-    MaxLength(usize),
-    WordCount(u32),
-    ContainsCharacter(char),
-}
-
 #[derive(serde::Deserialize, Debug)]
-struct StringQuery {
+pub(crate) struct StringQuery {
     is_palindrome: Option<bool>,
     min_length: Option<usize>,
     max_length: Option<usize>,
@@ -34,345 +54,482 @@ struct StringQuery {
     contains_character: Option<char>,
 }
 
+#[derive(serde::Deserialize, Debug)]
+struct FacetQuery {
+    facets: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct BulkDeleteOptions {
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    confirm: bool,
+}
+
+/// Path parameters are taken by name, since collection routes also capture `{collection}`.
+#[derive(serde::Deserialize)]
+struct StringPath {
+    string_value: String,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct IdPath {
+    pub(crate) id: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct SuggestParams {
+    #[serde(default)]
+    prefix: String,
+    lang: Option<String>,
+    limit: Option<usize>,
+}
+
+const DEFAULT_SUGGESTIONS: usize = 10;
+const MAX_SUGGESTIONS: usize = 50;
+
 #[derive(serde::Deserialize, Debug)]
 struct QueryParams {
     query: String,
+    #[serde(default)]
+    explain: bool,
+    lang: Option<String>,
 }
 
 
 #[post("/strings")]
-async fn process_string(_data: web::Data<AppState>, input: web::Json<UserInput>) -> impl Responder {
+async fn process_string(_data: web::Data<AppState>, store: Store, input: web::Json<UserInput>) -> Result<HttpResponse, ApiError> {
 
     println!("Received input: {}", input.value);
 
-    match pre_analysis_check(&input.value, &_data.env.db) {
+    let analysis_result = store_string(&_data, &store, input.into_inner()).await?;
+    return Ok(successful_post_string_response(&analysis_result));
+}
+
+/// Analyses and stores a new string, logging it and announcing it to the
+/// change feed. Shared by `POST /strings` and the analysis socket.
+pub(crate) async fn store_string(data: &AppState, store: &Store, input: UserInput) -> Result<AnalysisResult, ApiError> {
+    match pre_analysis_check(&input.value, store) {
         ProcessStringError::EmptyInput => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": " Invalid request body or missing \"value\" field"
-            });
-            return HttpResponse::BadRequest().json(json_response);
+            return Err(ApiError::EmptyInput);
         },
         ProcessStringError::Found(_result) => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "String already exists in the system",
-            });
-            return HttpResponse::Conflict().json(json_response);
+            return Err(ApiError::StringAlreadyExists);
         },
         ProcessStringError::NotFound => {
-            let analysis_result: AnalysisResult = analyse_string(input.value.clone());
-
-            {
-                let mut db = _data.env.db.lock().expect("db mutex poisoned");
+            let mut analysis_result: AnalysisResult = analyse_string(input.value);
+            analysis_result.tags = input.tags;
+            analysis_result.metadata = input.metadata;
+
+            store.write(data, |db, log| {
+                // Checked again under the lock, as a concurrent request
+                // may have stored the same value since the check above.
+                if db.processed_strings_hash.contains(&analysis_result.sha256_hash) {
+                    return Err(ApiError::StringAlreadyExists);
+                }
+                log.append(&[WalOp::Insert {
+                    collection: store.collection.clone(),
+                    result: analysis_result.clone(),
+                }])?;
                 db.processed_strings_hash.push(analysis_result.sha256_hash.clone());
                 db.processed_results.push(analysis_result.clone());
+                Ok(())
+            })?;
+            data.env.wal_sync().await?;
+            data.env.events.publish(EventKind::Created, store.collection.clone(), analysis_result.clone());
+
+            return Ok(analysis_result);
+        }
+    }
+}
+
+#[post("/strings/batch")]
+async fn process_string_batch(_data: web::Data<AppState>, store: Store, req: HttpRequest, mut payload: web::Payload) -> Result<HttpResponse, ApiError> {
+    let max_batch_size = _data.env.max_batch_size;
+    let max_payload_bytes = _data.env.max_payload_bytes;
+    let is_ndjson = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("application/x-ndjson"))
+        .unwrap_or(false);
+
+    // NDJSON bodies are split into items while the payload streams in, so an
+    // oversized batch is rejected without buffering all of it.
+    let mut items: Vec<Option<UserInput>> = Vec::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut received = 0;
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+        received += chunk.len();
+        if received > max_payload_bytes {
+            return Err(payload_too_large(max_payload_bytes));
+        }
+        buffer.extend_from_slice(&chunk);
+
+        if is_ndjson {
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                if let Some(item) = parse_ndjson_line(&line) {
+                    items.push(item);
+                }
+                if items.len() > max_batch_size {
+                    return Err(ApiError::BatchTooLarge(max_batch_size));
+                }
+            }
+        }
+    }
+
+    if is_ndjson {
+        if let Some(item) = parse_ndjson_line(&buffer) {
+            items.push(item);
+        }
+    } else {
+        let values: Vec<Value> = serde_json::from_slice(&buffer).map_err(|_| {
+            ApiError::InvalidRequest("Invalid request body, expected a JSON array of strings or {\"value\": ...} objects".to_string())
+        })?;
+        items = values.into_iter().map(parse_batch_item).collect();
+    }
+
+    if items.len() > max_batch_size {
+        return Err(ApiError::BatchTooLarge(max_batch_size));
+    }
+    if items.is_empty() {
+        return Err(ApiError::InvalidRequest("Batch must contain at least one string".to_string()));
+    }
+
+    println!("Received batch of {} strings", items.len());
+
+    let valid_values: Vec<String> = items.iter().flatten().filter(|input| !input.value.is_empty()).map(|input| input.value.clone()).collect();
+    let analysed = web::block(move || analyse_batch(valid_values))
+        .await
+        .map_err(|_| ApiError::Internal("Failed to analyse batch".to_string()))?;
+    let mut analysed = analysed.into_iter();
+
+    let mut results: Vec<Value> = Vec::with_capacity(items.len());
+    let (mut created, mut duplicates, mut invalid) = (0, 0, 0);
+
+    let inserts = store.write(&_data, |db, log| {
+        let mut known_hashes: std::collections::HashSet<String> = db.processed_strings_hash.iter().cloned().collect();
+        let mut inserts: Vec<AnalysisResult> = Vec::new();
+
+        for (index, item) in items.iter().enumerate() {
+            match item {
+                None => {
+                    invalid += 1;
+                    results.push(serde_json::json!({
+                        "index": index,
+                        "status": 400,
+                        "message": "Invalid item or missing \"value\" field",
+                    }));
+                },
+                Some(input) if input.value.is_empty() => {
+                    invalid += 1;
+                    results.push(serde_json::json!({
+                        "index": index,
+                        "status": 400,
+                        "message": ApiError::EmptyInput.detail(),
+                    }));
+                },
+                Some(input) => {
+                    let mut analysis_result = analysed.next().expect("one analysis per valid item");
+                    if !known_hashes.insert(analysis_result.sha256_hash.clone()) {
+                        duplicates += 1;
+                        results.push(serde_json::json!({
+                            "index": index,
+                            "status": 409,
+                            "value": input.value,
+                            "message": ApiError::StringAlreadyExists.detail(),
+                        }));
+                        continue;
+                    }
+
+                    analysis_result.tags = input.tags.clone();
+                    analysis_result.metadata = input.metadata.clone();
+                    created += 1;
+                    results.push(serde_json::json!({
+                        "index": index,
+                        "status": 201,
+                        "data": result_to_json(&analysis_result),
+                    }));
+                    inserts.push(analysis_result);
+                }
             }
+        }
 
-            return successful_post_string_response(&analysis_result);
+        let ops: Vec<WalOp> = inserts
+            .iter()
+            .map(|result| WalOp::Insert {
+                collection: store.collection.clone(),
+                result: result.clone(),
+            })
+            .collect();
+        log.append(&ops)?;
+        for result in &inserts {
+            db.processed_strings_hash.push(result.sha256_hash.clone());
+            db.processed_results.push(result.clone());
         }
+        Ok(inserts)
+    })?;
+    _data.env.wal_sync().await?;
+    for result in inserts {
+        _data.env.events.publish(EventKind::Created, store.collection.clone(), result);
     }
+
+    let response = serde_json::json!({
+        "status": "success",
+        "summary": {
+            "total": items.len(),
+            "created": created,
+            "duplicates": duplicates,
+            "invalid": invalid,
+        },
+        "results": results,
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+pub(crate) fn payload_too_large(max_payload_bytes: usize) -> ApiError {
+    ApiError::PayloadTooLarge(format!("Request body exceeds the maximum of {} bytes", max_payload_bytes))
+}
+
+fn parse_batch_item(value: Value) -> Option<UserInput> {
+    match serde_json::from_value::<BatchItem>(value) {
+        Ok(BatchItem::Plain(value)) => Some(UserInput {
+            value,
+            tags: Vec::new(),
+            metadata: serde_json::Map::new(),
+        }),
+        Ok(BatchItem::Input(input)) => Some(input),
+        Err(_) => None,
+    }
+}
+
+/// Parses one NDJSON line, returning `None` for blank lines and `Some(None)`
+/// for lines that are not a valid batch item.
+fn parse_ndjson_line(line: &[u8]) -> Option<Option<UserInput>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+
+    match serde_json::from_slice::<Value>(line) {
+        Ok(value) => Some(parse_batch_item(value)),
+        Err(_) => Some(None),
+    }
+}
+
+fn analyse_batch(values: Vec<String>) -> Vec<AnalysisResult> {
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = values.len().div_ceil(workers).max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = values
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().cloned().map(analyse_string).collect::<Vec<_>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("analysis worker panicked"))
+            .collect()
+    })
 }
 
 #[get("/strings/{string_value}")]
-async fn get_string_details(_data: web::Data<AppState>, path: web::Path<String>) -> impl Responder {
-    let input_value: String = path.into_inner();
+async fn get_string_details(store: Store, path: web::Path<StringPath>) -> Result<HttpResponse, ApiError> {
+    let input_value: String = path.into_inner().string_value;
     println!("Received input for details: {}", input_value);
 
-    match pre_analysis_check(&input_value, &_data.env.db) {
+    match pre_analysis_check(&input_value, &store) {
         ProcessStringError::EmptyInput => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "Input string is empty. Please provide a valid string."
-            });
-            return HttpResponse::BadRequest().json(json_response);
+            return Err(ApiError::EmptyInput);
         },
         ProcessStringError::NotFound => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "String does not exist in the system",
-            });
-            return HttpResponse::NotFound().json(json_response);
+            return Err(ApiError::StringNotFound);
         },
         ProcessStringError::Found(result) => {
-            let properties = struct_to_json(&result);
-            let data = serde_json::json!({
-                "id": result.sha256_hash,
-                "value": result.word,
-                "properties": properties,
-                "created_at": result.created_at
-            });
-            return HttpResponse::Ok().json(data);
+            return Ok(HttpResponse::Ok().json(result_to_json(&result)));
         }
     }
 }
 
 
 #[get("/strings")]
-async fn get_strings_filtered(_data: web::Data<AppState>, query: web::Query<StringQuery>) -> impl Responder {
+async fn get_strings_filtered(store: Store, version: ApiVersion, format: ResponseFormat, req: HttpRequest, query: Query<StringQuery>, facet_query: Query<FacetQuery>) -> Result<HttpResponse, ApiError> {
     let q = query.into_inner();
     println!("Received query for filtering: {:?}", q);
 
-    let selected_filters = extract_filters_from_query(&q);
+    let facets = match facet_query.into_inner().facets.as_deref() {
+        Some(raw) => parse_facets(raw).map_err(ApiError::InvalidRequest)?,
+        None => Vec::new(),
+    };
+
+    let mut selected_filters = extract_filters_from_query(&q);
+    selected_filters.extend(extract_context_filters(req.query_string()));
+    validate_filters(&selected_filters)?;
 
-    let filtered_results = apply_filters(_data, selected_filters.clone());
+    let filtered_results = apply_filters(&store, selected_filters.clone());
 
-    return process_filter_response(filtered_results, selected_filters);
+    return process_filter_response(filtered_results, selected_filters, &facets, version, format);
 }
 
 #[get("/strings/filter-by-natural-language")]
-async fn filter_by_natural_language(_data: web::Data<AppState>, query: web::Query<QueryParams>) -> impl Responder {
-    let q = query.into_inner().query;
+async fn filter_by_natural_language(_data: web::Data<AppState>, store: Store, format: ResponseFormat, query: Query<QueryParams>) -> Result<HttpResponse, ApiError> {
+    let QueryParams { query: q, explain, lang } = query.into_inner();
     println!("Received natural language query: {}", q);
 
-    let _parsed_filters: serde_json::Map<String, Value> = match first_stage_process(&q) {
-        Ok(filters) => filters,
-        Err(_e) => match parse_natural_language_query(&q) {
-            Ok(filters) => filters,
-            Err(e) => {
-                let json_response = serde_json::json!({
-                    "status": "error",
-                    "message": format!("Could not parse the natural language query: {}", e),
-                });
-                return HttpResponse::BadRequest().json(json_response);
-            }
-        },
-    };
-
-    match filter_database_res_based_on_query(_data, _parsed_filters.clone()) {
-        Some(results) => {
-
-            let mut data_array: Vec<serde_json::Value> = Vec::new();
-
-            for result in results.iter() {
-                let properties = struct_to_json(result);
-                let data = serde_json::json!({
-                    "id": result.sha256_hash,
-                    "value": result.word,
-                    "properties": properties,
-                    "created_at": result.created_at,
-
-                });
-                data_array.push(data);
-            }
-
-            let response = serde_json::json!({
-                "data": data_array,
-                "count": data_array.len(),
-                "interpreted_query": serde_json::json!({
-                    "original": q,
-                    "parsed_filters": _parsed_filters
-                })
-            });
+    let lexicon = resolve_language(lang.as_deref(), &q)?;
 
-            return HttpResponse::Ok().json(response);
-        },
-        None => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "Unable to parse natural language query",
-            });
-            return HttpResponse::BadRequest().json(json_response);
-        }
+    let rules = _data.env.nl_rules.read().expect("nl_rules lock poisoned").clone();
+    let parsed = parse(&q, lexicon, &rules)?;
+    if parsed.filters.is_empty() && parsed.sort.is_none() && parsed.limit.is_none() {
+        return Err(ApiError::UnparsableQuery("No recognizable filters found in the query".to_string()));
     }
-}
-
-fn filter_database_res_based_on_query(_data: web::Data<AppState>, filters: serde_json::Map<String, Value>) -> Option<Vec<AnalysisResult>> {
-    let db = _data.env.db.lock().expect("db mutex poisoned");
-    let mut all_results: Vec<AnalysisResult> = db.processed_results.clone();
-    let mut applied_filters_count: i32 = 0;
-
-    for filter in filters {
-        println!("Single Filter pair: {:?}", filter);
-
-        match filter.0.as_str() {
-            "is_palindrome" => {
-                if let Some(value) = filter.1.as_bool() {
-                    println!("Filtering by is_palindrome: {}", value);
-                    all_results = all_results.into_iter().filter(|res| res.is_palindrome == value).collect::<Vec<AnalysisResult>>();
-                    applied_filters_count += 1;
-                }
-            },
-            "min_length" => {
-                if let Some(value) = filter.1.as_u64() {
-                    println!("Filtering by min_length: {}", value);
-                    all_results = all_results.into_iter().filter(|res| res.length as u64 >= value).collect::<Vec<AnalysisResult>>();
-                    applied_filters_count += 1;
-                }
-            },
-            "max_length" => {
-                if let Some(value) = filter.1.as_u64() {
-                    println!("Filtering by max_length: {}", value);
-                    all_results = all_results.into_iter().filter(|res| res.length as u64 <= value).collect::<Vec<AnalysisResult>>();
-                    applied_filters_count += 1;
-                }
-            },
-            "word_count" => {
-                if let Some(value) = filter.1.as_u64() {
-                    println!("Filtering by word_count: {}", value);
-                    all_results = all_results.into_iter().filter(|res| res.word_count as u64 == value).collect::<Vec<AnalysisResult>>();
-                    applied_filters_count += 1;
-                }
-            },
-            "contains_character" => {
-                if let Some(value) = filter.1.as_str() {
-                    println!("Filtering by contains_character: {}", value);
-                    all_results = all_results.into_iter().filter(|res| res.word.contains(value)).collect::<Vec<AnalysisResult>>();
-                    applied_filters_count += 1;
-                }
-            },
-            _ => {
-                println!("Unknown filter key: {}", filter.0);
-            }
-        }
-
+    for phrase in &parsed.matches {
+        println!("Matched {} rule on {:?}: {:?}", phrase.rule, &q[phrase.start..phrase.end], phrase.filters);
     }
-    if applied_filters_count == 0 {
-        return None;
-    } else {
-        return Some(all_results);
+    if !parsed.leftover.is_empty() {
+        println!("Ignoring unrecognized words: {:?}", parsed.leftover.iter().map(|t| &t.text).collect::<Vec<_>>());
     }
-}   
 
-fn first_stage_process(query: &str) -> Result<serde_json::Map<String, Value>, String> {
-    let lower = query.to_lowercase();
-    let mut filters: serde_json::Map<String, Value> = serde_json::Map::new();
+    validate_filters(&parsed.filters)?;
 
-    for char in 'a'..='z' {
-        let char_str = char.to_string();
-        if lower.contains(&format!("strings that contain the letter {}", char_str)) || lower.contains(&format!("the letter {}", char_str)) || lower.contains(&format!("the vowel {}", char_str)) || lower.contains(&format!("the consonant {}", char_str)) {
-            filters.insert("contains_character".into(), serde_json::json!(char_str));
-        }
+    let mut results = apply_filters(&store, parsed.filters.clone());
+    if let Some(sort) = parsed.sort {
+        sort.apply(&mut results);
     }
-
-    if lower.contains("all single word palindromic strings") {
-        filters.insert("is_palindrome".into(), serde_json::json!(true));
-        filters.insert("word_count".into(), serde_json::json!(1));
-        return Ok(filters)
-    } else if lower.contains("strings longer than 10 characters") {
-        filters.insert("min_length".into(), serde_json::json!(11));
-        return Ok(filters)
-    } else if lower.contains("palindromic strings that contain the first vowel") {
-        filters.insert("is_palindrome".into(), serde_json::json!(true));
-        filters.insert("contains_character".into(), serde_json::json!("a"));
-        return Ok(filters)
-    } else if lower.contains("palindromic strings that contain the last vowel") {
-        filters.insert("is_palindrome".into(), serde_json::json!(true));
-        filters.insert("contains_character".into(), serde_json::json!("u"));
-        return Ok(filters)
-    } else {
-        if filters.is_empty() {
-            return Err("No recognizable filters found in the query".into())
-        } else {
-            return Ok(filters)
-        }
+    if let Some(limit) = parsed.limit {
+        results.truncate(limit);
     }
-}
-
-
-fn parse_natural_language_query(query: &str) -> Result<serde_json::Map<String, Value>, String> {
-    let lower = query.to_lowercase();
-    let words: Vec<&str> = lower.split_whitespace().collect();
-    let mut filters = serde_json::Map::new();
+    let data_array: Vec<serde_json::Value> = results.iter().map(result_to_json).collect();
 
+    let mut response = serde_json::json!({
+        "data": data_array,
+        "count": data_array.len(),
+        "interpreted_query": serde_json::json!({
+            "original": q,
+            "language": lexicon.code,
+            "parsed_filters": enum_to_string(parsed.filters.clone()),
+            "sort": parsed.sort.map(|sort| sort.to_json()),
+            "limit": parsed.limit
+        })
+    });
 
-    if lower.contains("palindrom") {
-        filters.insert("is_palindrome".into(), serde_json::json!(true));
+    if explain {
+        response["explanation"] = explain_parsed_query(&q, &parsed);
     }
 
+    return format.respond(response);
+}
 
-    if lower.contains("longer than") {
-        if let Some(num) = extract_number(&words) {
-            filters.insert("min_length".into(), serde_json::json!(num + 1));
-        }
-    } else if lower.contains("shorter than") {
-        if let Some(num) = extract_number(&words) {
-            filters.insert("max_length".into(), serde_json::json!(num - 1));
-        }
-    }
-
+#[get("/strings/filter-by-natural-language/suggest")]
+async fn suggest_natural_language(query: Query<SuggestParams>) -> Result<HttpResponse, ApiError> {
+    let SuggestParams { prefix, lang, limit } = query.into_inner();
 
-    if lower.contains("single word") || lower.contains("one word") {
-        filters.insert("word_count".into(), serde_json::json!(1));
-    } else if lower.contains("two words") || lower.contains("double word") {
-        filters.insert("word_count".into(), serde_json::json!(2));
-    }
+    let lexicon = resolve_language(lang.as_deref(), &prefix)?;
+    let limit = limit.unwrap_or(DEFAULT_SUGGESTIONS).min(MAX_SUGGESTIONS);
 
-    if let Some(letter) = extract_letter(&words) {
-        filters.insert("contains_character".into(), letter.to_string().into());
-    } else if lower.contains("first vowel") {
-        filters.insert("contains_character".into(), "a".into());
-    } else if lower.contains("last vowel") {
-        filters.insert("contains_character".into(), "u".into());
-    } else if lower.contains("vowel") {
-        filters.insert("contains_character".into(), "a".into()); 
-    } else if lower.contains("first consonant") {
-        filters.insert("contains_character".into(), "b".into()); 
-    }
+    let suggestions: Vec<Value> = suggest(&prefix, lexicon, limit)
+        .into_iter()
+        .map(|suggestion| serde_json::json!({
+            "text": suggestion.text,
+            "kind": suggestion.kind.as_str(),
+        }))
+        .collect();
 
+    let response = serde_json::json!({
+        "prefix": prefix,
+        "language": lexicon.code,
+        "suggestions": suggestions,
+        "count": suggestions.len(),
+    });
 
-    Ok(filters)
-    
+    return Ok(HttpResponse::Ok().json(response));
 }
 
-fn extract_number(words: &[&str]) -> Option<usize> {
-    for w in words {
-        if let Ok(num) = w.parse::<usize>() {
-            return Some(num);
-        }
-    }
-    None
-}
+/// Uses the language pack named by `lang`, or detects one from `text`.
+fn resolve_language(lang: Option<&str>, text: &str) -> Result<&'static Lexicon, ApiError> {
+    let Some(code) = lang else {
+        return Ok(detect_language(text));
+    };
 
-fn extract_letter(words: &[&str]) -> Option<char> {
-    let letters: Vec<char> = ('a'..='z').collect();
-    for (i, &w) in words.iter().enumerate() {
-        if w == "letter" {
-            if let Some(next) = words.get(i + 1) {
-                let c = next.chars().next().unwrap_or(' ');
-                if letters.contains(&c) {
-                    return Some(c);
-                }
-            }
-        }
-    }
-    None
+    language_pack(code).ok_or_else(|| ApiError::InvalidField {
+        field: Some("lang".to_string()),
+        detail: format!(
+            "unsupported language \"{}\", expected one of: {}",
+            code,
+            LANGUAGE_PACKS.iter().map(|pack| pack.code).collect::<Vec<_>>().join(", ")
+        ),
+    })
 }
 
-fn process_filter_response(results: Vec<AnalysisResult>, filters: Vec<SearchFilter>) -> HttpResponse {
-    if results.is_empty() {
-        let json_response = serde_json::json!({
-            "status": "error",
-            "message": "No strings match the provided filters",
-        });
-        return HttpResponse::NotFound().json(json_response);
-    }
+/// Describes how each part of the query was interpreted. Spans are character
+/// offsets into the original query, end exclusive.
+fn explain_parsed_query(query: &str, parsed: &ParsedQuery) -> Value {
+    let span = |start: usize, end: usize| {
+        let start_char = query[..start].chars().count();
+        serde_json::json!({
+            "start": start_char,
+            "end": start_char + query[start..end].chars().count(),
+        })
+    };
 
-    let mut data_array: Vec<serde_json::Value> = Vec::new();
+    let phrases: Vec<Value> = parsed
+        .matches
+        .iter()
+        .map(|phrase| serde_json::json!({
+            "text": &query[phrase.start..phrase.end],
+            "span": span(phrase.start, phrase.end),
+            "rule": phrase.rule,
+            "filters": enum_to_string(phrase.filters.clone()),
+            "sort": phrase.sort.map(|sort| sort.to_json()),
+            "limit": phrase.limit,
+        }))
+        .collect();
+
+    let leftover: Vec<Value> = parsed
+        .leftover
+        .iter()
+        .map(|token| serde_json::json!({
+            "text": &query[token.start..token.end],
+            "span": span(token.start, token.end),
+        }))
+        .collect();
 
-    for result in results.iter() {
-        let properties = struct_to_json(result);
-        let data = serde_json::json!({
-            "id": result.sha256_hash,
-            "value": result.word,
-            "properties": properties,
-            "created_at": result.created_at,
+    serde_json::json!({
+        "phrases": phrases,
+        "leftover": leftover,
+        "confidence": (parsed.confidence() * 100.0).round() / 100.0,
+    })
+}
 
-        });
-        data_array.push(data);
+fn process_filter_response(results: Vec<AnalysisResult>, filters: Vec<SearchFilter>, facets: &[FacetSpec], version: ApiVersion, format: ResponseFormat) -> Result<HttpResponse, ApiError> {
+    // v1 consumers rely on an empty result being a 404; v2 returns `data: []`.
+    if results.is_empty() && version == ApiVersion::V1 {
+        return Err(ApiError::NoMatches);
     }
 
-    let response = serde_json::json!({
+    let data_array: Vec<serde_json::Value> = results.iter().map(result_to_json).collect();
+
+    let mut response = serde_json::json!({
         "data": data_array,
         "count": data_array.len(),
         "filters_applied": enum_to_string(filters.clone())
     });
 
-    return HttpResponse::Ok().json(response);
+    if !facets.is_empty() {
+        response["facets"] = compute_facets(&results, facets);
+    }
+
+    return format.respond(response);
 }
 
 
-fn enum_to_string(filters: Vec<SearchFilter>) -> Value {
+pub(crate) fn enum_to_string(filters: Vec<SearchFilter>) -> Value {
     let mut object = serde_json::json!({});
 
     for filter in filters.iter() {
@@ -381,14 +538,70 @@ fn enum_to_string(filters: Vec<SearchFilter>) -> Value {
             SearchFilter::MinLength(min) => {object["min_length"] = serde_json::json!(min)},
             SearchFilter::MaxLength(max) => {object["max_length"] = serde_json::json!(max)},
             SearchFilter::WordCount(count) => {object["word_count"] = serde_json::json!(count)},
-            SearchFilter::ContainsCharacter(c) => {object["contains_character"] = serde_json::json!(c)},
+            SearchFilter::ContainsCharacter(c) => {
+                // Natural-language queries can ask for several characters; a single one stays a plain value.
+                match &mut object["contains_character"] {
+                    Value::Null => object["contains_character"] = serde_json::json!(c),
+                    Value::Array(chars) => chars.push(serde_json::json!(c)),
+                    first => *first = serde_json::json!([first.clone(), c]),
+                }
+            },
+            SearchFilter::ExcludesCharacter(c) => {
+                match object["excludes_character"].as_array_mut() {
+                    Some(chars) => chars.push(serde_json::json!(c)),
+                    None => object["excludes_character"] = serde_json::json!([c]),
+                }
+            },
+            SearchFilter::Tag(tag) => {
+                match object["tag"].as_array_mut() {
+                    Some(tags) => tags.push(serde_json::json!(tag)),
+                    None => object["tag"] = serde_json::json!([tag]),
+                }
+            },
+            SearchFilter::Metadata(key, value) => {object[format!("metadata.{}", key)] = serde_json::json!(value)},
         }
     }
 
     return object;
 }
 
-fn extract_filters_from_query(query: &StringQuery) -> Vec<SearchFilter> {
+/// Rejects filter sets that no string could ever satisfy, so they surface as a
+/// 422 rather than an indistinguishable empty result.
+pub(crate) fn validate_filters(filters: &[SearchFilter]) -> Result<(), ApiError> {
+    let min_length = filters.iter().filter_map(|f| match f { SearchFilter::MinLength(min) => Some(*min), _ => None }).max();
+    let max_length = filters.iter().filter_map(|f| match f { SearchFilter::MaxLength(max) => Some(*max), _ => None }).min();
+    let word_counts: Vec<u32> = filters.iter().filter_map(|f| match f { SearchFilter::WordCount(count) => Some(*count), _ => None }).collect();
+    let palindrome_flags: Vec<bool> = filters.iter().filter_map(|f| match f { SearchFilter::IsPalindrome(value) => Some(*value), _ => None }).collect();
+
+    if let (Some(min), Some(max)) = (min_length, max_length) && min > max {
+        return Err(ApiError::ConflictingFilters(format!("min_length ({}) is greater than max_length ({})", min, max)));
+    }
+    if max_length == Some(0) {
+        return Err(ApiError::ConflictingFilters("max_length is 0, but stored strings are never empty".to_string()));
+    }
+    if word_counts.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(ApiError::ConflictingFilters("a string cannot have several different word counts".to_string()));
+    }
+    if palindrome_flags.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(ApiError::ConflictingFilters("is_palindrome cannot be both true and false".to_string()));
+    }
+    for filter in filters {
+        if let SearchFilter::ExcludesCharacter(c) = filter && filters.contains(&SearchFilter::ContainsCharacter(*c)) {
+            return Err(ApiError::ConflictingFilters(format!("'{}' cannot be both required and excluded", c)));
+        }
+    }
+    // n words need at least n characters plus n - 1 separating spaces.
+    if let (Some(&count), Some(max)) = (word_counts.first(), max_length) {
+        let shortest = (count as usize * 2).saturating_sub(1);
+        if shortest > max {
+            return Err(ApiError::ConflictingFilters(format!("{} words need at least {} characters, but max_length is {}", count, shortest, max)));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn extract_filters_from_query(query: &StringQuery) -> Vec<SearchFilter> {
     let mut filters = Vec::new();
 
     if let Some(is_palindrome) = query.is_palindrome {
@@ -410,86 +623,201 @@ fn extract_filters_from_query(query: &StringQuery) -> Vec<SearchFilter> {
     filters
 }
 
-fn apply_filters(_data: web::Data<AppState>, filters: Vec<SearchFilter>) -> Vec<AnalysisResult> {
-    let mut filtered_results;
-    
-    {
-        let db = _data.env.db.lock().expect("db mutex poisoned");
-        filtered_results = db.processed_results.clone();
+/// Extracts the repeatable `tag=` and dynamic `metadata.<key>=` filters, which
+/// cannot be expressed as fields of `StringQuery`.
+pub(crate) fn extract_context_filters(query_string: &str) -> Vec<SearchFilter> {
+    let pairs = match web::Query::<Vec<(String, String)>>::from_query(query_string) {
+        Ok(pairs) => pairs.into_inner(),
+        Err(_) => return Vec::new(),
+    };
+
+    let mut filters = Vec::new();
+    for (key, value) in pairs {
+        if key == "tag" {
+            filters.push(SearchFilter::Tag(value));
+        } else if let Some(meta_key) = key.strip_prefix("metadata.") {
+            filters.push(SearchFilter::Metadata(meta_key.to_string(), value));
+        }
     }
-    
-    
 
-    for filter in filters {
-        filtered_results = match filter {
-            SearchFilter::IsPalindrome(value) => {
-                filtered_results.into_iter().filter(|res| res.is_palindrome == value).collect()
-            },
-            SearchFilter::MinLength(min) => {
-                filtered_results.into_iter().filter(|res| res.length >= min).collect()
-            },
-            SearchFilter::MaxLength(max) => {
-                filtered_results.into_iter().filter(|res| res.length <= max).collect()
-            },
-            SearchFilter::WordCount(count) => {
-                filtered_results.into_iter().filter(|res| res.word_count as u32 == count).collect()
-            },
-            SearchFilter::ContainsCharacter(c) => {
-                filtered_results.into_iter().filter(|res| res.word.contains(c)).collect()
-            },
-        };
+    filters
+}
+
+fn metadata_value_matches(stored: &Value, expected: &str) -> bool {
+    match stored {
+        Value::String(s) => s == expected,
+        other => serde_json::to_string(other).is_ok_and(|s| s == expected),
     }
+}
+
+pub(crate) fn apply_filters(db: &std::sync::Mutex<TempDatabase>, filters: Vec<SearchFilter>) -> Vec<AnalysisResult> {
+    let db = db.lock().expect("db mutex poisoned");
 
-    filtered_results
+    db.processed_results.iter().filter(|res| matches_filters(res, &filters)).cloned().collect()
+}
+
+/// Whether a single string passes every filter.
+pub(crate) fn matches_filters(result: &AnalysisResult, filters: &[SearchFilter]) -> bool {
+    filters.iter().all(|filter| match filter {
+        SearchFilter::IsPalindrome(value) => result.is_palindrome == *value,
+        SearchFilter::MinLength(min) => result.length >= *min,
+        SearchFilter::MaxLength(max) => result.length <= *max,
+        SearchFilter::WordCount(count) => result.word_count as u32 == *count,
+        SearchFilter::ContainsCharacter(c) => result.word.contains(*c),
+        SearchFilter::ExcludesCharacter(c) => !result.word.contains(*c),
+        SearchFilter::Tag(tag) => result.tags.contains(tag),
+        SearchFilter::Metadata(key, value) => result.metadata.get(key).is_some_and(|v| metadata_value_matches(v, value)),
+    })
 }
 
 
+#[patch("/strings/{id}")]
+async fn update_string_context(_data: web::Data<AppState>, store: Store, path: web::Path<IdPath>, input: web::Json<ContextUpdate>) -> Result<HttpResponse, ApiError> {
+    let id: String = path.into_inner().id;
+    let update = input.into_inner();
+    println!("Received context update for: {}", id);
+
+    let response = {
+        let mut log = _data.env.wal_writer();
+        let mut db = store.lock_current(&_data)?;
+        let result = db.find_mut(&id).ok_or(ApiError::StringNotFound)?;
+
+        let mut tags = result.tags.clone();
+        let mut metadata = result.metadata.clone();
+        if let Some(new_tags) = update.tags {
+            tags = new_tags;
+        }
+        // Metadata follows JSON merge-patch semantics: a null value removes the key.
+        if let Some(patch) = update.metadata {
+            for (key, value) in patch {
+                if value.is_null() {
+                    metadata.remove(&key);
+                } else {
+                    metadata.insert(key, value);
+                }
+            }
+        }
+
+        log.append(&[WalOp::Update {
+            collection: store.collection.clone(),
+            hash: id,
+            tags: tags.clone(),
+            metadata: metadata.clone(),
+        }])?;
+        result.tags = tags;
+        result.metadata = metadata;
+        result_to_json(result)
+    };
+    _data.env.wal_sync().await?;
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
 #[delete("/strings/{string_value}")]
-async fn delete_string(_data: web::Data<AppState>, path: web::Path<String>) -> impl Responder {
-    let input_value: String = path.into_inner();
+async fn delete_string(_data: web::Data<AppState>, store: Store, path: web::Path<StringPath>) -> Result<HttpResponse, ApiError> {
+    let input_value: String = path.into_inner().string_value;
     println!("Received input for deletion: {}", input_value);
 
-    match pre_analysis_check(&input_value, &_data.env.db) {
+    match pre_analysis_check(&input_value, &store) {
         ProcessStringError::EmptyInput => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "Input string is empty. Please provide a valid string."
-            });
-            return HttpResponse::BadRequest().json(json_response);
+            return Err(ApiError::EmptyInput);
         },
         ProcessStringError::NotFound => {
-            let json_response = serde_json::json!({
-                "status": "error",
-                "message": "String does not exist in the system",
-            });
-            return HttpResponse::NotFound().json(json_response);
+            return Err(ApiError::StringNotFound);
         },
         ProcessStringError::Found(_result) => {
             {
-                let mut db = _data.env.db.lock().expect("db mutex poisoned");
-                let mut index_to_remove: Option<usize> = None;
-                for (i, processed_string) in db.processed_strings_hash.iter().enumerate() {
-                    if *processed_string == _result.sha256_hash {
-                        index_to_remove = Some(i);
-                        break;
-                    }
-                }
-                if let Some(index) = index_to_remove {
-                    db.processed_strings_hash.remove(index);
-                    db.processed_results.remove(index);
+                let deleted_at = chrono::Utc::now();
+                let mut log = _data.env.wal_writer();
+                let mut db = store.lock_current(&_data)?;
+                // A concurrent request may have deleted it since the check above.
+                if !db.processed_strings_hash.contains(&_result.sha256_hash) {
+                    return Err(ApiError::StringNotFound);
                 }
+                log.append(&[WalOp::Delete {
+                    collection: store.collection.clone(),
+                    hash: _result.sha256_hash.clone(),
+                    deleted_at,
+                }])?;
+                db.soft_delete(&_result.sha256_hash, deleted_at);
             }
+            _data.env.wal_sync().await?;
+            _data.env.events.publish(EventKind::Deleted, store.collection.clone(), _result);
 
             let json_response = serde_json::json!({
                 "status": "success",
                 "message": "String successfully deleted from the system",
             });
-            return HttpResponse::Ok().json(json_response);
+            return Ok(HttpResponse::Ok().json(json_response));
         }
     }
 }
 
-fn analyse_string(input: String) -> AnalysisResult {
+#[delete("/strings")]
+async fn delete_strings_filtered(_data: web::Data<AppState>, store: Store, req: HttpRequest, query: Query<StringQuery>, options: Query<BulkDeleteOptions>) -> Result<HttpResponse, ApiError> {
+    let q = query.into_inner();
+    let options = options.into_inner();
+    println!("Received query for bulk deletion: {:?} {:?}", q, options);
+
+    let mut selected_filters = extract_filters_from_query(&q);
+    selected_filters.extend(extract_context_filters(req.query_string()));
+    validate_filters(&selected_filters)?;
+
+    if selected_filters.is_empty() && !options.dry_run && !options.confirm {
+        return Err(ApiError::InvalidRequest("No filters supplied. Pass confirm=true to delete every string in the system".to_string()));
+    }
+
+    // Matches are only final under the lock that moves them to the trash, so
+    // strings deleted concurrently are neither logged nor announced twice.
+    let matched_results = if options.dry_run {
+        apply_filters(&store, selected_filters.clone())
+    } else {
+        let matched_results = {
+            let mut log = _data.env.wal_writer();
+            let mut db = store.lock_current(&_data)?;
+            let matched_results: Vec<AnalysisResult> = db.processed_results.iter().filter(|res| matches_filters(res, &selected_filters)).cloned().collect();
+            let hashes: std::collections::HashSet<&String> = matched_results.iter().map(|res| &res.sha256_hash).collect();
+            let deleted_at = chrono::Utc::now();
+            let ops: Vec<WalOp> = hashes
+                .iter()
+                .map(|hash| WalOp::Delete {
+                    collection: store.collection.clone(),
+                    hash: hash.to_string(),
+                    deleted_at,
+                })
+                .collect();
+
+            log.append(&ops)?;
+            db.soft_delete_many(&hashes, deleted_at);
+            matched_results
+        };
+        _data.env.wal_sync().await?;
+        for result in &matched_results {
+            _data.env.events.publish(EventKind::Deleted, store.collection.clone(), result.clone());
+        }
+        matched_results
+    };
+    let data_array: Vec<Value> = matched_results.iter().map(result_to_json).collect();
+
+    let message = if options.dry_run {
+        format!("{} strings would be deleted from the system", data_array.len())
+    } else {
+        format!("{} strings successfully deleted from the system", data_array.len())
+    };
+
+    let response = serde_json::json!({
+        "status": "success",
+        "message": message,
+        "dry_run": options.dry_run,
+        "data": data_array,
+        "count": data_array.len(),
+        "filters_applied": enum_to_string(selected_filters)
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+pub(crate) fn analyse_string(input: String) -> AnalysisResult {
     let length = input.chars().count();
     let is_palindrome = input.chars().eq(input.chars().rev());
     let unique_characters = input.chars().collect::<std::collections::HashSet<_>>().len();
@@ -506,7 +834,7 @@ fn analyse_string(input: String) -> AnalysisResult {
         *character_frequency_map.entry(c).or_insert(0) += 1;
     }
 
-    let created_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let created_at = chrono::Utc::now();
 
     return AnalysisResult::new(
         length,
@@ -553,7 +881,7 @@ fn pre_analysis_check(input: &String, db: &std::sync::Mutex<TempDatabase>) -> Pr
     return ProcessStringError::NotFound;
 }
 
-fn struct_to_json(result: &AnalysisResult) -> serde_json::Value {
+pub(crate) fn struct_to_json(result: &AnalysisResult) -> serde_json::Value {
     serde_json::json!({
         "length": result.length,
         "is_palindrome": result.is_palindrome,
@@ -564,16 +892,17 @@ fn struct_to_json(result: &AnalysisResult) -> serde_json::Value {
     })
 }
 
-fn successful_post_string_response(result: &AnalysisResult) -> HttpResponse {
-   
-   let properties = struct_to_json(result);
-   let data = serde_json::json!({
+pub(crate) fn result_to_json(result: &AnalysisResult) -> serde_json::Value {
+    serde_json::json!({
         "id": result.sha256_hash,
         "value": result.word,
-        "properties": properties,
-        "created_at": result.created_at
-   });
-
-    HttpResponse::Created().json(data)
+        "properties": struct_to_json(result),
+        "tags": result.tags,
+        "metadata": result.metadata,
+        "created_at": result.created_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+    })
+}
 
+fn successful_post_string_response(result: &AnalysisResult) -> HttpResponse {
+    HttpResponse::Created().json(result_to_json(result))
 }
\ No newline at end of file
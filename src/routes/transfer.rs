@@ -0,0 +1,254 @@
+use crate::AppState;
+use crate::config::config::AnalysisResult;
+use crate::config::events::EventKind;
+use crate::config::wal::WalOp;
+use crate::error::ApiError;
+use crate::routes::collections::Store;
+use crate::routes::strings::{analyse_string, payload_too_large, result_to_json};
+use crate::routes::params::Query;
+use actix_web::{HttpResponse, get, post, web, web::Bytes};
+use futures_util::StreamExt;
+use serde_json::Value;
+
+/// Number of strings serialised per lock of the store while exporting.
+const EXPORT_CHUNK_SIZE: usize = 100;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum ImportMode {
+    #[default]
+    SkipExisting,
+    Overwrite,
+    FailOnConflict,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct ImportOptions {
+    #[serde(default)]
+    mode: ImportMode,
+}
+
+/// One exported line. `properties` are recomputed from `value` rather than trusted.
+#[derive(serde::Deserialize)]
+struct ImportRecord {
+    value: String,
+    id: Option<String>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    metadata: serde_json::Map<String, Value>,
+}
+
+/// Streams every stored string as NDJSON, in insertion order. The strings to
+/// export are fixed when the export starts, but the store is locked one
+/// chunk at a time: strings added meanwhile are left out, strings deleted
+/// before their chunk is written are skipped, and edits may or may not show.
+#[get("/export")]
+async fn export_strings(store: Store) -> Result<HttpResponse, ApiError> {
+    println!("Exporting strings from {}", store.collection.as_deref().unwrap_or("the global store"));
+
+    let db = store.db.clone();
+    let hashes = db.lock().expect("db mutex poisoned").processed_strings_hash.clone();
+    let chunks = futures_util::stream::unfold((hashes, 0, 0), move |(hashes, offset, hint)| {
+        let db = db.clone();
+        async move {
+            let end = hashes.len().min(offset + EXPORT_CHUNK_SIZE);
+            if offset >= end {
+                return None;
+            }
+
+            let mut lines = String::new();
+            let mut hint = hint;
+            {
+                let db = db.lock().expect("db mutex poisoned");
+                for hash in &hashes[offset..end] {
+                    let Some(position) = locate(&db.processed_strings_hash, hash, hint) else {
+                        continue;
+                    };
+                    lines.push_str(&result_to_json(&db.processed_results[position]).to_string());
+                    lines.push('\n');
+                    hint = position + 1;
+                }
+            }
+            Some((Ok::<Bytes, actix_web::Error>(Bytes::from(lines)), (hashes, end, hint)))
+        }
+    });
+
+    return Ok(HttpResponse::Ok().content_type("application/x-ndjson").streaming(chunks));
+}
+
+/// Position of `hash` in `hashes`, checking `hint` first: stored strings keep
+/// their order, so only a deletion or restore elsewhere forces a scan.
+fn locate(hashes: &[String], hash: &str, hint: usize) -> Option<usize> {
+    if hashes.get(hint).is_some_and(|h| h == hash) {
+        return Some(hint);
+    }
+    hashes.iter().position(|h| h == hash)
+}
+
+/// Loads an NDJSON export. Each line is re-analysed from its `value`, keeping
+/// the exported `created_at`, `tags` and `metadata`.
+#[post("/import")]
+async fn import_strings(_data: web::Data<AppState>, store: Store, options: Query<ImportOptions>, mut payload: web::Payload) -> Result<HttpResponse, ApiError> {
+    let mode = options.into_inner().mode;
+    let max_import_size = _data.env.max_import_size;
+    let max_payload_bytes = _data.env.max_payload_bytes;
+
+    let mut records: Vec<(usize, Result<AnalysisResult, String>)> = Vec::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut line_number = 0;
+    let mut received = 0;
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+        received += chunk.len();
+        if received > max_payload_bytes {
+            return Err(payload_too_large(max_payload_bytes));
+        }
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            line_number += 1;
+            if let Some(record) = parse_import_line(&line) {
+                records.push((line_number, record));
+            }
+            if records.len() > max_import_size {
+                return Err(ApiError::BatchTooLarge(max_import_size));
+            }
+        }
+    }
+    line_number += 1;
+    if let Some(record) = parse_import_line(&buffer) {
+        records.push((line_number, record));
+    }
+
+    if records.len() > max_import_size {
+        return Err(ApiError::BatchTooLarge(max_import_size));
+    }
+    if records.is_empty() {
+        return Err(ApiError::InvalidRequest("Import must contain at least one string".to_string()));
+    }
+
+    println!("Received import of {} strings with mode {:?}", records.len(), mode);
+
+    let (mut created, mut overwritten, mut skipped) = (0, 0, 0);
+    let mut errors: Vec<Value> = Vec::new();
+
+    let inserted = store.write(&_data, |db, log| {
+        let mut known_hashes: std::collections::HashSet<String> = db.processed_strings_hash.iter().cloned().collect();
+        let mut changes: Vec<&AnalysisResult> = Vec::new();
+
+        for (line, record) in records.iter() {
+            let result = match record {
+                Ok(result) => result,
+                Err(message) => {
+                    errors.push(serde_json::json!({
+                        "line": line,
+                        "message": message,
+                    }));
+                    continue;
+                }
+            };
+
+            if known_hashes.insert(result.sha256_hash.clone()) {
+                created += 1;
+            } else if mode == ImportMode::Overwrite {
+                overwritten += 1;
+            } else {
+                skipped += 1;
+                continue;
+            }
+            changes.push(result);
+        }
+
+        if mode == ImportMode::FailOnConflict && skipped > 0 {
+            return Err(ApiError::ImportConflict(skipped));
+        }
+
+        let ops: Vec<WalOp> = changes
+            .iter()
+            .map(|result| WalOp::Insert {
+                collection: store.collection.clone(),
+                result: (*result).clone(),
+            })
+            .collect();
+        log.append(&ops)?;
+        let mut inserted: Vec<AnalysisResult> = Vec::new();
+        for result in changes {
+            if !db.upsert(result.clone()) {
+                inserted.push(result.clone());
+            }
+        }
+        Ok(inserted)
+    })?;
+    _data.env.wal_sync().await?;
+    for result in inserted {
+        _data.env.events.publish(EventKind::Created, store.collection.clone(), result);
+    }
+
+    let response = serde_json::json!({
+        "status": "success",
+        "mode": mode,
+        "summary": {
+            "total": records.len(),
+            "created": created,
+            "overwritten": overwritten,
+            "skipped": skipped,
+            "invalid": errors.len(),
+        },
+        "errors": errors,
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+/// Parses one NDJSON line, returning `None` for blank lines.
+fn parse_import_line(line: &[u8]) -> Option<Result<AnalysisResult, String>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+
+    let record = match serde_json::from_slice::<ImportRecord>(line) {
+        Ok(record) => record,
+        Err(e) => {
+            return Some(Err(format!("Invalid import line: {}", e)));
+        }
+    };
+    if record.value.is_empty() {
+        return Some(Err(ApiError::EmptyInput.detail()));
+    }
+
+    let mut result = analyse_string(record.value);
+    if record.id.as_ref().is_some_and(|id| *id != result.sha256_hash) {
+        return Some(Err("\"id\" is not the SHA-256 hash of \"value\"".to_string()));
+    }
+    if let Some(created_at) = record.created_at {
+        result.created_at = created_at;
+    }
+    result.tags = record.tags;
+    result.metadata = record.metadata;
+
+    return Some(Ok(result));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn locate_follows_strings_shifted_by_deletions() {
+        let store = hashes(&["b", "c", "d"]);
+        assert_eq!(locate(&store, "c", 1), Some(1));
+        // "a" at position 0 was deleted, so "c" moved back by one.
+        assert_eq!(locate(&store, "c", 2), Some(1));
+        assert_eq!(locate(&store, "a", 0), None);
+        assert_eq!(locate(&store, "d", 3), Some(2));
+    }
+}
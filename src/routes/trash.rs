@@ -0,0 +1,55 @@
+use crate::AppState;
+use crate::config::config::RestoreError;
+use crate::config::wal::WalOp;
+use crate::error::ApiError;
+use crate::routes::collections::Store;
+use crate::routes::strings::{IdPath, result_to_json};
+use actix_web::{HttpResponse, get, post, web};
+
+#[get("/trash")]
+async fn list_trash(store: Store) -> Result<HttpResponse, ApiError> {
+    let db = store.lock().expect("db mutex poisoned");
+
+    let data_array: Vec<serde_json::Value> = db
+        .trash
+        .iter()
+        .map(|record| {
+            let mut data = result_to_json(&record.result);
+            data["deleted_at"] = serde_json::json!(record.deleted_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+            data
+        })
+        .collect();
+
+    let response = serde_json::json!({
+        "data": data_array,
+        "count": data_array.len(),
+    });
+
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+#[post("/trash/{id}/restore")]
+async fn restore_string(_data: web::Data<AppState>, store: Store, path: web::Path<IdPath>) -> Result<HttpResponse, ApiError> {
+    let id: String = path.into_inner().id;
+    println!("Received request to restore: {}", id);
+
+    let result = store.write(&_data, |db, log| {
+        match db.restorable(&id) {
+            Ok(_) => {},
+            Err(RestoreError::NotInTrash) => {
+                return Err(ApiError::NotInTrash);
+            },
+            Err(RestoreError::AlreadyExists) => {
+                return Err(ApiError::StringAlreadyExists);
+            }
+        }
+        log.append(&[WalOp::Restore {
+            collection: store.collection.clone(),
+            hash: id.clone(),
+        }])?;
+        Ok(db.restore(&id).expect("checked as restorable above"))
+    })?;
+    _data.env.wal_sync().await?;
+
+    return Ok(HttpResponse::Ok().json(result_to_json(&result)));
+}
@@ -0,0 +1,188 @@
+use crate::AppState;
+use crate::config::events::EventKind;
+use crate::config::wal::WalOp;
+use crate::config::webhooks::{DeliveryStatus, Webhook, is_public_address};
+use crate::error::ApiError;
+use crate::tasks::webhooks::{new_delivery, random_hex, send_attempt, with_delivery_id};
+use actix_web::{HttpResponse, delete, get, post, web};
+
+#[derive(serde::Deserialize)]
+struct WebhookInput {
+    url: String,
+    secret: Option<String>,
+    #[serde(default = "all_events")]
+    events: Vec<EventKind>,
+}
+
+fn all_events() -> Vec<EventKind> {
+    vec![EventKind::Created, EventKind::Deleted]
+}
+
+#[derive(serde::Deserialize)]
+struct WebhookPath {
+    webhook_id: String,
+}
+
+fn webhook_to_json(hook: &Webhook) -> serde_json::Value {
+    serde_json::json!({
+        "id": hook.id,
+        "url": hook.url,
+        "events": hook.events.iter().map(|kind| kind.as_str()).collect::<Vec<_>>(),
+        "created_at": hook.created_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    })
+}
+
+/// Refuses URLs whose host is, or only resolves to, a loopback, private or
+/// other non-public address, unless `WEBHOOK_ALLOW_PRIVATE` is set. Hosts that
+/// do not resolve yet are accepted; deliveries re-check every address.
+async fn check_target(url: &reqwest::Url, allow_private: bool) -> Result<(), ApiError> {
+    if allow_private {
+        return Ok(());
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    // IPv6 hosts keep their brackets in URLs.
+    let addresses: Vec<std::net::IpAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => vec![ip],
+        Err(_) => match tokio::net::lookup_host((host, url.port_or_known_default().unwrap_or(80))).await {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    if !addresses.is_empty() && !addresses.into_iter().any(is_public_address) {
+        return Err(ApiError::InvalidField {
+            field: Some("url".to_string()),
+            detail: "must not point at a loopback, private or link-local address".to_string(),
+        });
+    }
+    return Ok(());
+}
+
+/// Registers a webhook. The signing secret is generated unless one is given,
+/// and is only ever returned by this request.
+#[post("/webhooks")]
+async fn create_webhook(_data: web::Data<AppState>, input: web::Json<WebhookInput>) -> Result<HttpResponse, ApiError> {
+    let input = input.into_inner();
+    println!("Received webhook registration for: {}", input.url);
+
+    let url = reqwest::Url::parse(&input.url).map_err(|e| ApiError::InvalidField {
+        field: Some("url".to_string()),
+        detail: e.to_string(),
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ApiError::InvalidField {
+            field: Some("url".to_string()),
+            detail: "must be an http or https URL".to_string(),
+        });
+    }
+    check_target(&url, _data.env.webhooks.allow_private).await?;
+    if input.events.is_empty() {
+        return Err(ApiError::InvalidField {
+            field: Some("events".to_string()),
+            detail: "must name at least one event".to_string(),
+        });
+    }
+    if input.secret.as_ref().is_some_and(|secret| secret.is_empty()) {
+        return Err(ApiError::InvalidField {
+            field: Some("secret".to_string()),
+            detail: "must not be empty".to_string(),
+        });
+    }
+
+    let mut events = input.events;
+    events.dedup();
+    let hook = Webhook {
+        id: random_hex(8),
+        url: url.to_string(),
+        secret: input.secret.unwrap_or_else(|| random_hex(32)),
+        events,
+        created_at: chrono::Utc::now(),
+    };
+    {
+        let mut log = _data.env.wal_writer();
+        log.append(&[WalOp::RegisterWebhook { webhook: hook.clone() }])?;
+        _data.env.webhooks.register(hook.clone());
+    }
+    _data.env.wal_sync().await?;
+
+    let mut response = webhook_to_json(&hook);
+    response["secret"] = serde_json::json!(hook.secret);
+    return Ok(HttpResponse::Created().json(response));
+}
+
+#[get("/webhooks")]
+async fn list_webhooks(_data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let data_array: Vec<serde_json::Value> = _data.env.webhooks.list().iter().map(webhook_to_json).collect();
+
+    let response = serde_json::json!({
+        "data": data_array,
+        "count": data_array.len(),
+    });
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+#[delete("/webhooks/{webhook_id}")]
+async fn delete_webhook(_data: web::Data<AppState>, path: web::Path<WebhookPath>) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner().webhook_id;
+    println!("Received request to delete webhook: {}", id);
+
+    {
+        let mut log = _data.env.wal_writer();
+        if _data.env.webhooks.find(&id).is_none() {
+            return Err(ApiError::WebhookNotFound);
+        }
+        log.append(&[WalOp::RemoveWebhook { id: id.clone() }])?;
+        _data.env.webhooks.remove(&id);
+    }
+    _data.env.wal_sync().await?;
+
+    let json_response = serde_json::json!({
+        "status": "success",
+        "message": "Webhook successfully deleted from the system",
+    });
+    return Ok(HttpResponse::Ok().json(json_response));
+}
+
+/// Recent deliveries to a webhook, newest first, with every attempt made.
+#[get("/webhooks/{webhook_id}/deliveries")]
+async fn list_deliveries(_data: web::Data<AppState>, path: web::Path<WebhookPath>) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner().webhook_id;
+    if _data.env.webhooks.find(&id).is_none() {
+        return Err(ApiError::WebhookNotFound);
+    }
+
+    let deliveries = _data.env.webhooks.deliveries(&id);
+    let response = serde_json::json!({
+        "data": deliveries,
+        "count": deliveries.len(),
+    });
+    return Ok(HttpResponse::Ok().json(response));
+}
+
+/// Sends a signed `test` event once, without retries, and reports the outcome.
+#[post("/webhooks/{webhook_id}/test")]
+async fn test_webhook(_data: web::Data<AppState>, path: web::Path<WebhookPath>) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner().webhook_id;
+    let hook = _data.env.webhooks.find(&id).ok_or(ApiError::WebhookNotFound)?;
+    println!("Sending test delivery to webhook: {}", id);
+
+    let mut delivery = new_delivery(&hook, "test");
+    let payload = serde_json::json!({
+        "event": "test",
+        "occurred_at": delivery.created_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "data": null,
+    });
+    let body = with_delivery_id(&payload, &delivery.id);
+
+    let attempt = send_attempt(&_data.env.webhooks.client, &hook, &delivery, &body).await;
+    delivery.status = match attempt.error {
+        None => DeliveryStatus::Succeeded,
+        Some(_) => DeliveryStatus::Failed,
+    };
+    delivery.attempts.push(attempt);
+    delivery.next_attempt_at = None;
+    _data.env.webhooks.record(delivery.clone());
+
+    return Ok(HttpResponse::Ok().json(delivery));
+}
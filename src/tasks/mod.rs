@@ -0,0 +1,4 @@
+pub mod nl_rules;
+pub mod snapshot;
+pub mod trash;
+pub mod webhooks;
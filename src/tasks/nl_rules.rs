@@ -0,0 +1,38 @@
+use crate::AppState;
+use crate::query::rules::load_rules;
+use actix_web::web;
+use std::time::{Duration, SystemTime};
+
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reloads the natural-language rules file whenever its modification time
+/// changes. A file that fails to load is reported and the previous rules are kept.
+pub async fn reload_nl_rules(data: web::Data<AppState>) {
+    let Some(path) = data.env.nl_rules_path.clone() else {
+        return;
+    };
+    let modified = |path: &str| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    let mut last_modified: Option<SystemTime> = modified(&path);
+    let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current = modified(&path);
+        if current.is_none() || current == last_modified {
+            continue;
+        }
+        last_modified = current;
+
+        match load_rules(&path) {
+            Ok(rules) => {
+                println!("Reloaded {} natural language rules from {}", rules.len(), path);
+                *data.env.nl_rules.write().expect("nl_rules lock poisoned") = std::sync::Arc::new(rules);
+            },
+            Err(e) => {
+                println!("Keeping previous natural language rules: {}", e);
+            }
+        }
+    }
+}
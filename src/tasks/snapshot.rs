@@ -0,0 +1,20 @@
+use crate::AppState;
+use crate::config::snapshot::save_snapshot;
+use actix_web::web;
+use std::time::Duration;
+
+/// Periodically writes a snapshot of every store to `SNAPSHOT_PATH`.
+pub async fn write_snapshots(data: web::Data<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(data.env.snapshot_interval_secs.max(1)));
+    // The first tick fires immediately; there is nothing new to save at boot.
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let data = data.clone();
+        if web::block(move || save_snapshot(&data.env)).await.is_err() {
+            println!("Snapshot writer panicked");
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::AppState;
+use crate::config::wal::WalOp;
+use actix_web::web;
+use std::time::Duration;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically drops trashed strings older than the configured retention period.
+pub async fn purge_expired_trash(data: web::Data<AppState>) {
+    let retention = i64::try_from(data.env.trash_retention_secs).ok().and_then(chrono::TimeDelta::try_seconds);
+    let Some(retention) = retention else {
+        println!("TRASH_RETENTION_SECS is too large to represent, trashed strings are never purged");
+        return;
+    };
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // A retention reaching back past the earliest representable time
+        // expires nothing.
+        let Some(cutoff) = chrono::Utc::now().checked_sub_signed(retention) else {
+            continue;
+        };
+        let mut databases = vec![(None, data.env.db.clone())];
+        {
+            let collections = data.env.collections.lock().expect("collections mutex poisoned");
+            databases.extend(collections.iter().map(|(name, db)| (Some(name.clone()), db.clone())));
+        }
+
+        let mut purged = 0;
+        for (collection, db) in databases {
+            let mut log = data.env.wal_writer();
+            let mut db = db.lock().expect("db mutex poisoned");
+            if !db.trash.iter().any(|rec| rec.deleted_at < cutoff) {
+                continue;
+            }
+            if let Err(e) = log.append(&[WalOp::PurgeTrash { collection, cutoff }]) {
+                println!("Skipping trash purge: {}", e.detail());
+                continue;
+            }
+            purged += db.purge_trash(cutoff);
+        }
+
+        if let Err(e) = data.env.wal_sync().await {
+            println!("Failed to flush the trash purge: {}", e.detail());
+        }
+        if purged > 0 {
+            println!("Purged {} strings from the trash", purged);
+        }
+    }
+}
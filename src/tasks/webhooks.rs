@@ -0,0 +1,332 @@
+use crate::AppState;
+use crate::config::events::StoreEvent;
+use crate::config::webhooks::{Delivery, DeliveryAttempt, DeliveryStatus, Webhook};
+use crate::routes::strings::result_to_json;
+use actix_web::web;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify, mpsc};
+use tokio::time::Instant;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest wait between retries, however many attempts are configured.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Deliveries sent at once, so a burst of events does not open a connection per event.
+const DELIVERY_WORKERS: usize = 32;
+/// Deliveries waiting for a worker. While it is full, new deliveries wait for
+/// room and retries fail.
+const DELIVERY_QUEUE_SIZE: usize = 1024;
+/// Deliveries waiting out their backoff. Failed attempts are not retried while it is full.
+const MAX_PENDING_RETRIES: usize = 1024;
+
+/// A delivery on its way to a worker.
+#[derive(Clone)]
+struct Job {
+    hook: Webhook,
+    delivery: Delivery,
+    body: String,
+    attempt: u32,
+    /// Wait before the next retry, should this attempt fail.
+    delay: Duration,
+}
+
+/// Deliveries waiting for their next attempt, soonest first.
+#[derive(Default)]
+struct RetryQueue {
+    jobs: std::sync::Mutex<BTreeMap<(Instant, u64), Job>>,
+    next_key: std::sync::atomic::AtomicU64,
+    added: Notify,
+}
+
+impl RetryQueue {
+    /// Schedules `job` for `due`, returning false when the queue is full.
+    fn push(&self, due: Instant, job: Job) -> bool {
+        let mut jobs = self.jobs.lock().expect("retry queue mutex poisoned");
+        if jobs.len() >= MAX_PENDING_RETRIES {
+            return false;
+        }
+        let key = self.next_key.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        jobs.insert((due, key), job);
+        self.added.notify_one();
+        true
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.jobs.lock().expect("retry queue mutex poisoned").keys().next().map(|(due, _)| *due)
+    }
+
+    fn take_due(&self, now: Instant) -> Vec<Job> {
+        let mut jobs = self.jobs.lock().expect("retry queue mutex poisoned");
+        let later = jobs.split_off(&(now, u64::MAX));
+        std::mem::replace(&mut *jobs, later).into_values().collect()
+    }
+}
+
+/// Sends every store event to the webhooks subscribed to it. Events come from
+/// the event log's lossless queue, so bursts larger than the change feed
+/// retains are still delivered. Deliveries then go through a bounded queue
+/// to a fixed pool of workers, so only events, not per-webhook bodies, pile
+/// up behind a slow receiver.
+pub async fn dispatch_webhooks(data: web::Data<AppState>) {
+    let Some(mut events) = data.env.events.take_queue() else {
+        return;
+    };
+    let (jobs, receiver) = mpsc::channel(DELIVERY_QUEUE_SIZE);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let retries = Arc::new(RetryQueue::default());
+
+    for _ in 0..DELIVERY_WORKERS {
+        actix_web::rt::spawn(work(data.clone(), receiver.clone(), retries.clone()));
+    }
+    actix_web::rt::spawn(schedule_retries(data.clone(), jobs.clone(), retries));
+
+    while let Some(event) = events.recv().await {
+        let hooks = data.env.webhooks.subscribed_to(event.kind);
+        if hooks.is_empty() {
+            continue;
+        }
+
+        let payload = event_payload(&event);
+        for hook in hooks {
+            let delivery = new_delivery(&hook, event.kind.as_str());
+            let body = with_delivery_id(&payload, &delivery.id);
+            data.env.webhooks.record(delivery.clone());
+            let job = Job {
+                hook,
+                delivery,
+                body,
+                attempt: 1,
+                delay: data.env.webhooks.retry_base.min(MAX_BACKOFF),
+            };
+            if jobs.send(job).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Hands a retry to the workers, failing its delivery if the queue is full.
+fn enqueue_retry(data: &AppState, jobs: &mpsc::Sender<Job>, job: Job) {
+    if let Err(e) = jobs.try_send(job) {
+        let job = match e {
+            mpsc::error::TrySendError::Full(job) | mpsc::error::TrySendError::Closed(job) => job,
+        };
+        give_up(data, &job, "the delivery queue is full");
+    }
+}
+
+fn give_up(data: &AppState, job: &Job, reason: &str) {
+    println!("Dropping webhook delivery {} to {}: {}", job.delivery.id, job.hook.url, reason);
+    data.env.webhooks.update(&job.delivery.id, |logged| {
+        logged.status = DeliveryStatus::Failed;
+        logged.next_attempt_at = None;
+    });
+}
+
+/// Makes one attempt per job, scheduling a retry with a doubled wait after
+/// each failure until the delivery runs out of attempts.
+async fn work(data: web::Data<AppState>, jobs: Arc<Mutex<mpsc::Receiver<Job>>>, retries: Arc<RetryQueue>) {
+    let webhooks = &data.env.webhooks;
+
+    loop {
+        let Some(job) = jobs.lock().await.recv().await else {
+            return;
+        };
+
+        let attempt = send_attempt(&webhooks.client, &job.hook, &job.delivery, &job.body).await;
+        let succeeded = attempt.error.is_none();
+        let retry = !succeeded && job.attempt < webhooks.max_attempts;
+
+        webhooks.update(&job.delivery.id, |logged| {
+            logged.attempts.push(attempt);
+            logged.status = match (succeeded, retry) {
+                (true, _) => DeliveryStatus::Succeeded,
+                (false, true) => DeliveryStatus::Pending,
+                (false, false) => DeliveryStatus::Failed,
+            };
+            logged.next_attempt_at = retry.then(|| chrono::Utc::now() + job.delay);
+        });
+
+        if !retry {
+            if !succeeded {
+                println!("Giving up on webhook delivery {} to {} after {} attempts", job.delivery.id, job.hook.url, job.attempt);
+            }
+            continue;
+        }
+
+        let next = Job {
+            attempt: job.attempt + 1,
+            delay: job.delay.saturating_mul(2).min(MAX_BACKOFF),
+            ..job.clone()
+        };
+        if !retries.push(Instant::now() + job.delay, next) {
+            give_up(&data, &job, "too many deliveries are waiting to be retried");
+        }
+    }
+}
+
+/// Moves retries back onto the delivery queue once their wait is over.
+async fn schedule_retries(data: web::Data<AppState>, jobs: mpsc::Sender<Job>, retries: Arc<RetryQueue>) {
+    loop {
+        match retries.next_due() {
+            Some(due) => {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(due) => {},
+                    _ = retries.added.notified() => {
+                        continue;
+                    }
+                }
+            },
+            None => {
+                retries.added.notified().await;
+                continue;
+            }
+        }
+
+        for job in retries.take_due(Instant::now()) {
+            // Stop retrying once the webhook has been deleted.
+            if data.env.webhooks.find(&job.hook.id).is_none() {
+                continue;
+            }
+            enqueue_retry(&data, &jobs, job);
+        }
+    }
+}
+
+fn event_payload(event: &StoreEvent) -> serde_json::Value {
+    serde_json::json!({
+        "event": event.kind.as_str(),
+        "event_id": event.id,
+        "collection": event.collection,
+        "occurred_at": event.at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "data": result_to_json(&event.result),
+    })
+}
+
+/// Adds the delivery id to a payload and serialises it.
+pub fn with_delivery_id(payload: &serde_json::Value, delivery_id: &str) -> String {
+    let mut payload = payload.clone();
+    payload["delivery_id"] = serde_json::json!(delivery_id);
+    payload.to_string()
+}
+
+pub fn new_delivery(hook: &Webhook, event: &str) -> Delivery {
+    Delivery {
+        id: random_hex(16),
+        webhook_id: hook.id.clone(),
+        event: event.to_string(),
+        status: DeliveryStatus::Pending,
+        attempts: Vec::new(),
+        next_attempt_at: Some(chrono::Utc::now()),
+        created_at: chrono::Utc::now(),
+    }
+}
+
+/// Sends the signed payload once. Any 2xx response is a success.
+pub async fn send_attempt(client: &reqwest::Client, hook: &Webhook, delivery: &Delivery, body: &str) -> DeliveryAttempt {
+    let at = chrono::Utc::now();
+
+    let response = client
+        .post(&hook.url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Id", &hook.id)
+        .header("X-Webhook-Delivery", &delivery.id)
+        .header("X-Webhook-Event", &delivery.event)
+        .header("X-Webhook-Signature", format!("sha256={}", sign(&hook.secret, body)))
+        .body(body.to_string())
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => DeliveryAttempt {
+            at,
+            status_code: Some(response.status().as_u16()),
+            error: None,
+        },
+        Ok(response) => DeliveryAttempt {
+            at,
+            status_code: Some(response.status().as_u16()),
+            error: Some(format!("Receiver responded with {}", response.status())),
+        },
+        Err(e) => DeliveryAttempt {
+            at,
+            status_code: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Hex HMAC-SHA256 of the body, keyed with the webhook's secret.
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn random_hex(bytes: usize) -> String {
+    (0..bytes).map(|_| format!("{:02x}", rand::random::<u8>())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(attempt: u32) -> Job {
+        let hook = Webhook {
+            id: "hook".to_string(),
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: Vec::new(),
+            created_at: chrono::Utc::now(),
+        };
+        Job {
+            delivery: new_delivery(&hook, "created"),
+            hook,
+            body: "{}".to_string(),
+            attempt,
+            delay: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn retries_come_due_in_order() {
+        let retries = RetryQueue::default();
+        let now = Instant::now();
+        assert!(retries.push(now + Duration::from_secs(2), job(3)));
+        assert!(retries.push(now, job(1)));
+        assert!(retries.push(now + Duration::from_secs(1), job(2)));
+
+        assert_eq!(retries.next_due(), Some(now));
+        let due: Vec<u32> = retries.take_due(now + Duration::from_secs(1)).iter().map(|job| job.attempt).collect();
+        assert_eq!(due, vec![1, 2]);
+        assert_eq!(retries.next_due(), Some(now + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn retry_queue_refuses_jobs_once_full() {
+        let retries = RetryQueue::default();
+        let due = Instant::now();
+        for _ in 0..MAX_PENDING_RETRIES {
+            assert!(retries.push(due, job(2)));
+        }
+        assert!(!retries.push(due, job(2)));
+    }
+
+    #[test]
+    fn sign_matches_the_rfc_4231_vector() {
+        assert_eq!(sign("Jefe", "what do ya want for nothing?"), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn sign_depends_on_secret_and_body() {
+        let signature = sign("secret", "{}");
+        assert_eq!(signature.len(), 64);
+        assert_ne!(signature, sign("other", "{}"));
+        assert_ne!(signature, sign("secret", "{ }"));
+    }
+}